@@ -0,0 +1,89 @@
+use nix::fcntl::OFlag;
+use nix::libc;
+use std::fs::{File, OpenOptions};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+
+use crate::error::{TUILogError, TUILogErrorMap, TUILogResult};
+use crate::utils::get_current_tty_path;
+
+// from linux/kd.h
+const KDGKBLED: libc::c_ulong = 0x4B64;
+const KDSKBLED: libc::c_ulong = 0x4B65;
+const K_NUMLOCK: libc::c_char = 0x02;
+const K_CAPSLOCK: libc::c_char = 0x04;
+// from linux/vt.h
+const VT_LOCKSWITCH: libc::c_ulong = 0x560B;
+const VT_UNLOCKSWITCH: libc::c_ulong = 0x560C;
+
+fn open_console() -> std::io::Result<File> {
+  OpenOptions::new()
+    .read(true)
+    .custom_flags(OFlag::O_NOCTTY.bits())
+    .open(get_current_tty_path()?)
+}
+
+// the lock flags of the keyboard, which the leds show by default
+fn get_keyboard_flags(console: &File) -> Option<libc::c_char> {
+  let mut flags: libc::c_char = 0;
+
+  unsafe {
+    if libc::ioctl(console.as_raw_fd(), KDGKBLED, &mut flags) < 0 {
+      return None;
+    }
+  }
+
+  Some(flags)
+}
+
+pub fn is_caps_lock_on() -> bool {
+  match open_console() {
+    Ok(console) => {
+      get_keyboard_flags(&console).is_some_and(|flags| flags & K_CAPSLOCK != 0)
+    }
+    Err(_) => false,
+  }
+}
+
+pub fn enable_num_lock() -> TUILogResult<()> {
+  let console = open_console().tuilog_err(TUILogError::NumLockFailed)?;
+  let flags =
+    get_keyboard_flags(&console).tuilog_err(TUILogError::NumLockFailed)?;
+
+  unsafe {
+    let flags = (flags | K_NUMLOCK) as libc::c_ulong;
+    if libc::ioctl(console.as_raw_fd(), KDSKBLED, flags) < 0 {
+      return Err(TUILogError::NumLockFailed);
+    }
+  }
+
+  Ok(())
+}
+
+// needs root, as switching is disabled for every virtual terminal
+pub fn set_vt_switching(enabled: bool) -> TUILogResult<()> {
+  let console = open_console().tuilog_err(TUILogError::VTLockFailed)?;
+  let request = if enabled {
+    VT_UNLOCKSWITCH
+  } else {
+    VT_LOCKSWITCH
+  };
+
+  unsafe {
+    if libc::ioctl(console.as_raw_fd(), request, 0) < 0 {
+      return Err(TUILogError::VTLockFailed);
+    }
+  }
+
+  Ok(())
+}
+
+// turns switching virtual terminals back on once dropped, even when the lock
+// screen panics
+pub struct VTSwitchingGuard;
+
+impl Drop for VTSwitchingGuard {
+  fn drop(&mut self) {
+    let _ = set_vt_switching(true);
+  }
+}
@@ -10,14 +10,18 @@ pub enum TUILogError {
   Unauthorized,
   UserNotFound,
   InvalidSessionOption,
+  SessionNotAllowed,
   ShellSessionFailed,
   ShellInputOutputSetupFailed,
   BackgroundArtFailed,
   PrivilegeDropFailed,
   EnvironmentSetupFailed,
+  HookFailed,
   ShutdownFailed,
   RebootFailed,
   DBUSConnectionFailed,
+  NumLockFailed,
+  VTLockFailed,
 }
 
 pub trait TUILogErrorMap<T> {
@@ -54,16 +58,24 @@ impl TUILogError {
         "Failed to redirect standard input and output to terminal."
       }
       TUILogError::InvalidSessionOption => "Invalid session selected.",
+      TUILogError::SessionNotAllowed => {
+        "The selected session is not available to this user."
+      }
       TUILogError::PrivilegeDropFailed => {
         "Failed to drop user priviledges for session."
       }
       TUILogError::EnvironmentSetupFailed => {
         "Failed to setup user environment for session."
       }
+      TUILogError::HookFailed => "A pre session hook failed.",
       TUILogError::BackgroundArtFailed => "Failed to draw background art.",
       TUILogError::ShutdownFailed => "Failed to shutdown system.",
       TUILogError::RebootFailed => "Failed to reboot.",
       TUILogError::DBUSConnectionFailed => "Failed to open DBUS connection.",
+      TUILogError::NumLockFailed => "Failed to turn on num lock.",
+      TUILogError::VTLockFailed => {
+        "Failed to lock switching virtual terminals."
+      }
     }
   }
 }
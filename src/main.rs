@@ -1,3 +1,4 @@
+pub mod console;
 pub mod error;
 pub mod session;
 pub mod state;
@@ -5,27 +6,90 @@ pub mod sys_ctrl;
 pub mod tui;
 pub mod utils;
 
+use crate::console::{enable_num_lock, set_vt_switching, VTSwitchingGuard};
 use crate::error::DrawTUILogResult;
+use crate::session::try_autologin;
+use crate::state::keyboard_config;
 use crate::tui::{
-  draw_background_ascii_art, draw_content_box, get_base_theme,
-  set_default_values,
+  animate_background_ascii_art, bind_global_keys, draw_background_ascii_art,
+  draw_content_box, draw_hint_bar, draw_lock_box, draw_notice, get_base_theme,
+  reset_on_idle, set_default_values, track_idle_time, watch_caps_lock,
 };
 
+use cursive::event::Event;
 use cursive::view::Resizable;
 use cursive::views::StackView;
+use std::env;
+use std::process::exit;
+use users::{get_current_uid, get_user_by_uid};
+
+// `tuilog lock [--all]` locks the running session until the password of the
+// invoking user is entered, --all also locks switching virtual terminals
+fn run_lock_screen(lock_all_vts: bool) {
+  let Some(user) = get_user_by_uid(get_current_uid()) else {
+    eprintln!("tuilog: no user found for the current uid");
+    exit(1);
+  };
+
+  let mut siv = cursive::default();
+  siv.set_theme(get_base_theme());
+  // the lock screen can only be left by unlocking it
+  siv.clear_global_callbacks(Event::CtrlChar('c'));
+
+  let mut stack = StackView::new();
+
+  let draw_background_result = draw_background_ascii_art(&mut stack);
+  draw_lock_box(&mut stack, user.name().to_string_lossy().to_string());
+  draw_background_result.draw_on_err(&mut siv);
+
+  siv.add_fullscreen_layer(stack.full_screen());
+  animate_background_ascii_art(&mut siv);
+  siv.focus_name("password").ok();
+  watch_caps_lock(&mut siv);
+  let vt_switching_guard = lock_all_vts.then_some(VTSwitchingGuard);
+  if lock_all_vts {
+    set_vt_switching(false).draw_on_err(&mut siv);
+  }
+
+  siv.run();
+
+  drop(vt_switching_guard);
+  // the lock screen may also be left without unlocking it, like on a closed
+  // terminal
+  if siv.take_user_data::<bool>() != Some(true) {
+    exit(1);
+  }
+}
 
 fn main() {
+  if env::args().nth(1).as_deref() == Some("lock") {
+    run_lock_screen(env::args().any(|arg| arg == "--all"));
+    return;
+  }
+
+  try_autologin();
+
   let mut siv = cursive::default();
   siv.set_theme(get_base_theme());
 
   let mut stack = StackView::new();
 
   let draw_background_result = draw_background_ascii_art(&mut stack);
+  draw_hint_bar(&mut stack);
   draw_content_box(&mut stack);
   draw_background_result.draw_on_err(&mut siv);
 
   siv.add_fullscreen_layer(stack.full_screen());
+  animate_background_ascii_art(&mut siv);
   set_default_values(&mut siv);
+  if keyboard_config.num_lock {
+    enable_num_lock().draw_on_err(&mut siv);
+  }
+  watch_caps_lock(&mut siv);
+  bind_global_keys(&mut siv);
+  track_idle_time(&mut siv);
+  draw_notice(&mut siv);
+  reset_on_idle(&mut siv);
 
   siv.run();
 }
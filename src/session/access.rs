@@ -0,0 +1,19 @@
+use users::User;
+
+use crate::state::{is_in_groups, Session};
+
+pub fn is_session_allowed(session: &Session, user: &User) -> bool {
+  if is_in_groups(user, &session.denied_groups) {
+    return false;
+  }
+  // sessions without any allow list are open to everyone
+  if session.allowed_users.is_empty() && session.allowed_groups.is_empty() {
+    return true;
+  }
+
+  session
+    .allowed_users
+    .iter()
+    .any(|username| user.name().to_str() == Some(username.as_str()))
+    || is_in_groups(user, &session.allowed_groups)
+}
@@ -0,0 +1,123 @@
+use nix::libc;
+use nix::sys::termios::{
+  tcflush, tcgetattr, tcsetattr, FlushArg, LocalFlags, SetArg,
+};
+use std::fs;
+use std::io::{stdin, stdout, Write};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+use crate::error::{TUILogError, TUILogErrorMap, TUILogResult};
+use crate::session::{
+  auth_user, is_session_allowed, run_pre_session_hooks, spawn_shell_session,
+};
+use crate::state::{
+  autologin_config, get_user_session, pam_services, sessions, Session,
+};
+use crate::utils::{get_current_tty, log_message};
+
+// cleared on every boot as /run is a tmpfs
+const AUTOLOGIN_MARKER_DIR: &str = "/run/tuilog";
+
+// waits for a key press for the given seconds, the key is discarded so it
+// doesn't reach the greeter
+fn is_key_pressed(seconds: u64) -> bool {
+  let stdin = stdin();
+  let Ok(original_termios) = tcgetattr(&stdin) else {
+    return false;
+  };
+
+  let mut termios = original_termios.clone();
+  termios
+    .local_flags
+    .remove(LocalFlags::ICANON | LocalFlags::ECHO);
+  if tcsetattr(&stdin, SetArg::TCSANOW, &termios).is_err() {
+    return false;
+  }
+
+  let mut poll_fd = libc::pollfd {
+    fd: stdin.as_raw_fd(),
+    events: libc::POLLIN,
+    revents: 0,
+  };
+  let timeout = (seconds * 1000).min(i32::MAX as u64) as i32;
+  let pressed = unsafe { libc::poll(&mut poll_fd, 1, timeout) } > 0;
+
+  let _ = tcflush(&stdin, FlushArg::TCIFLUSH);
+  let _ = tcsetattr(&stdin, SetArg::TCSANOW, &original_termios);
+
+  pressed
+}
+
+// true only the first time it is called on the tty since boot
+fn is_first_start() -> bool {
+  let tty = get_current_tty().unwrap_or_default();
+  let marker =
+    Path::new(AUTOLOGIN_MARKER_DIR).join(format!("autologin-{}", tty));
+  if marker.exists() {
+    return false;
+  }
+
+  let _ = fs::create_dir_all(AUTOLOGIN_MARKER_DIR);
+  let _ = fs::write(marker, "");
+
+  true
+}
+
+fn get_autologin_session(username: &str) -> Option<&'static Session> {
+  let find_session =
+    |name: &str| sessions.iter().find(|session| session.name == name);
+
+  match autologin_config.session {
+    Some(ref session_name) => find_session(session_name),
+    None => get_user_session(username)
+      .and_then(|session_name| find_session(&session_name))
+      .or(sessions.first()),
+  }
+}
+
+fn autologin(username: &str) -> TUILogResult<()> {
+  let session = get_autologin_session(username)
+    .tuilog_err(TUILogError::InvalidSessionOption)?;
+  let (pam_client, user) = auth_user(&pam_services.autologin, username, "")?;
+  if !is_session_allowed(session, &user) {
+    return Err(TUILogError::SessionNotAllowed);
+  }
+
+  run_pre_session_hooks(&user, session)?;
+  spawn_shell_session(&user, session)?;
+
+  drop(pam_client); // Close the PAM session
+  Ok(())
+}
+
+// logs in the configured user before the greeter is shown, the greeter takes
+// over once the session exits or if the autologin is skipped
+pub fn try_autologin() {
+  let Some(ref username) = autologin_config.user else {
+    return;
+  };
+  if let Some(ref tty) = autologin_config.tty {
+    if get_current_tty().as_ref() != Some(tty) {
+      return;
+    }
+  }
+  if autologin_config.only_first_start && !is_first_start() {
+    return;
+  }
+
+  if autologin_config.grace_period > 0 {
+    println!(
+      "Logging in as {}, press any key for the login screen...",
+      username
+    );
+    let _ = stdout().flush();
+    if is_key_pressed(autologin_config.grace_period) {
+      return;
+    }
+  }
+
+  if let Err(error) = autologin(username) {
+    log_message(&format!("Autologin failed: {}", error.message()));
+  }
+}
@@ -6,28 +6,106 @@ use nix::unistd::{
 };
 use std::env;
 use std::ffi::CString;
-use std::path::Path;
+use std::path::PathBuf;
 use users::os::unix::UserExt;
 use users::User;
 
 use crate::error::{TUILogError, TUILogErrorMap, TUILogResult};
+use crate::state::{environment, Session};
 use crate::utils::get_current_tty_path;
 
-pub fn set_env(user: &User) -> TUILogResult<()> {
+// expands $HOME and $USER (or ${HOME} and ${USER}), other variables are kept
+// as they are
+fn expand_user_vars(value: &str, user: &User) -> String {
+  let mut expanded = String::with_capacity(value.len());
+  let mut rest = value;
+
+  while let Some(start) = rest.find('$') {
+    expanded.push_str(&rest[..start]);
+    rest = &rest[start + 1..];
+
+    let (name, len) = match rest.strip_prefix('{') {
+      Some(braced) => match braced.find('}') {
+        Some(end) => (&braced[..end], end + 2),
+        None => ("", 0),
+      },
+      None => {
+        let end = rest
+          .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+          .unwrap_or(rest.len());
+        (&rest[..end], end)
+      }
+    };
+
+    match name {
+      "HOME" => expanded.push_str(&user.home_dir().to_string_lossy()),
+      "USER" => expanded.push_str(&user.name().to_string_lossy()),
+      _ => {
+        expanded.push('$');
+        continue;
+      }
+    }
+    rest = &rest[len..];
+  }
+  expanded.push_str(rest);
+
+  expanded
+}
+
+// the variables set for the user on top of the greeter's environment, the
+// session's `unset_env` is removed afterwards
+pub fn get_user_env(
+  user: &User,
+  session: &Session,
+) -> TUILogResult<Vec<(String, String)>> {
   let shell_path = user
     .shell()
     .to_str()
     .tuilog_err(TUILogError::EnvironmentSetupFailed)?;
-  env::set_var("USER", user.name());
-  env::set_var("LOGNAME", user.name());
-  env::set_var("HOME", user.home_dir());
-  env::set_var("SHELL", shell_path);
-  env::set_var("TERM", "linux");
-
-  // Set XDG variables if running a desktop session
-  env::set_var("XDG_SESSION_TYPE", "tty");
-  env::set_var("XDG_RUNTIME_DIR", format!("/run/user/{}", user.uid()));
-  env::set_current_dir(Path::new(user.home_dir()))
+  let username = user.name().to_string_lossy().to_string();
+
+  let mut user_env = vec![
+    ("USER".to_string(), username.clone()),
+    ("LOGNAME".to_string(), username),
+    (
+      "HOME".to_string(),
+      user.home_dir().to_string_lossy().to_string(),
+    ),
+    ("SHELL".to_string(), shell_path.to_string()),
+    ("TERM".to_string(), "linux".to_string()),
+    // Set XDG variables if running a desktop session
+    ("XDG_SESSION_TYPE".to_string(), "tty".to_string()),
+    (
+      "XDG_RUNTIME_DIR".to_string(),
+      format!("/run/user/{}", user.uid()),
+    ),
+  ];
+
+  // the global environment comes first so sessions can override it
+  for (key, value) in environment.iter().chain(session.env.iter()) {
+    user_env.push((key.clone(), expand_user_vars(value, user)));
+  }
+
+  Ok(user_env)
+}
+
+// the directory the session starts in
+pub fn get_user_cwd(user: &User, session: &Session) -> PathBuf {
+  match session.cwd {
+    Some(ref cwd) => PathBuf::from(expand_user_vars(cwd, user)),
+    None => user.home_dir().to_path_buf(),
+  }
+}
+
+pub fn set_env(user: &User, session: &Session) -> TUILogResult<()> {
+  for (key, value) in get_user_env(user, session)? {
+    env::set_var(key, value);
+  }
+  for key in &session.unset_env {
+    env::remove_var(key);
+  }
+
+  env::set_current_dir(get_user_cwd(user, session))
     .tuilog_err(TUILogError::EnvironmentSetupFailed)?;
 
   Ok(())
@@ -80,3 +158,29 @@ pub fn set_process_ids(user: &User) -> TUILogResult<()> {
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn test_user() -> User {
+    User::new(1000, "alice", 1000).with_home_dir("/home/alice")
+  }
+
+  #[test]
+  fn expands_user_vars() {
+    let user = test_user();
+    assert_eq!(expand_user_vars("$HOME/bin", &user), "/home/alice/bin");
+    assert_eq!(expand_user_vars("${USER}_x", &user), "alice_x");
+    assert_eq!(expand_user_vars("$USER:$HOME", &user), "alice:/home/alice");
+  }
+
+  #[test]
+  fn keeps_other_vars() {
+    let user = test_user();
+    assert_eq!(expand_user_vars("$PATH:$HOME", &user), "$PATH:/home/alice");
+    assert_eq!(expand_user_vars("$USERNAME", &user), "$USERNAME");
+    assert_eq!(expand_user_vars("${HOME", &user), "${HOME");
+    assert_eq!(expand_user_vars("cost $", &user), "cost $");
+  }
+}
@@ -0,0 +1,120 @@
+use nix::libc;
+use nix::unistd::{getgrouplist, setgid, setgroups, setuid, Gid, Uid};
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use users::User;
+
+use crate::error::{TUILogError, TUILogErrorMap, TUILogResult};
+use crate::session::{get_user_cwd, get_user_env};
+use crate::state::{hooks, Hook, HookUser, Session};
+use crate::utils::{get_current_tty, log_message};
+
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// runs the command as the user, with the groups and environment of the
+// user's session
+fn set_hook_user(
+  command: &mut Command,
+  user: &User,
+  session: &Session,
+) -> TUILogResult<()> {
+  let uid = Uid::from_raw(user.uid());
+  let gid = Gid::from_raw(user.primary_group_id());
+  let c_username = CString::new(user.name().as_bytes())
+    .tuilog_err(TUILogError::PrivilegeDropFailed)?;
+  // looked up before forking as only the system calls are safe in between
+  // forking and running the command
+  let groups = getgrouplist(&c_username, gid)
+    .tuilog_err(TUILogError::PrivilegeDropFailed)?;
+
+  for key in &session.unset_env {
+    command.env_remove(key);
+  }
+  command
+    .envs(get_user_env(user, session)?)
+    .current_dir(get_user_cwd(user, session));
+
+  unsafe {
+    command.pre_exec(move || {
+      setgroups(&groups)?;
+      setgid(gid)?;
+      setuid(uid)?;
+      Ok(())
+    });
+  }
+
+  Ok(())
+}
+
+fn run_hook(hook: &Hook, user: &User, session: &Session) -> bool {
+  let mut command = Command::new("/bin/sh");
+  command
+    .arg("-c")
+    .arg(&hook.exec)
+    .env("USER", user.name())
+    .env("TTY", get_current_tty().unwrap_or_default())
+    .env("SESSION", &session.name)
+    // the whole group is killed on timing out, not only the shell
+    .process_group(0)
+    // the greeter still owns the terminal while the hooks run
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null());
+
+  if hook.run_as == HookUser::User
+    && set_hook_user(&mut command, user, session).is_err()
+  {
+    log_message(&format!("Failed to set up session hook: {}", hook.exec));
+    return false;
+  }
+
+  let Ok(mut child) = command.spawn() else {
+    log_message(&format!("Failed to run session hook: {}", hook.exec));
+    return false;
+  };
+
+  let deadline = Instant::now() + Duration::from_secs(hook.timeout);
+  loop {
+    match child.try_wait() {
+      Ok(Some(status)) => {
+        if !status.success() {
+          log_message(&format!("Session hook failed: {}", hook.exec));
+        }
+        return status.success();
+      }
+      Ok(None) if Instant::now() < deadline => sleep(HOOK_POLL_INTERVAL),
+      _ => {
+        unsafe {
+          libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+        }
+        let _ = child.wait();
+        log_message(&format!("Session hook timed out: {}", hook.exec));
+        return false;
+      }
+    }
+  }
+}
+
+pub fn run_pre_session_hooks(
+  user: &User,
+  session: &Session,
+) -> TUILogResult<()> {
+  for hook in &hooks.pre_session {
+    if !run_hook(hook, user, session) && hooks.fail_closed {
+      return Err(TUILogError::HookFailed);
+    }
+  }
+
+  Ok(())
+}
+
+// failures are only logged as the session has already ended
+pub fn run_post_session_hooks(user: &User, session: &Session) {
+  for hook in &hooks.post_session {
+    run_hook(hook, user, session);
+  }
+}
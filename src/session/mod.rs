@@ -1,7 +1,13 @@
+mod access;
+mod autologin;
 mod env;
+mod hooks;
 mod shell;
 mod start;
 
+pub use access::*;
+pub use autologin::*;
 pub use env::*;
+pub use hooks::*;
 pub use shell::*;
 pub use start::*;
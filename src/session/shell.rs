@@ -1,3 +1,4 @@
+use nix::libc;
 use nix::sys::wait::waitpid;
 use nix::unistd::{execvp, fork, ForkResult};
 use std::ffi::CString;
@@ -5,8 +6,9 @@ use users::os::unix::UserExt;
 use users::User;
 
 use crate::error::{TUILogError, TUILogErrorMap, TUILogResult};
-use crate::session::{set_env, set_process_ids};
+use crate::session::{run_post_session_hooks, set_env, set_process_ids};
 use crate::state::Session;
+use crate::utils::log_message;
 
 fn spawn_session(user: &User, session: &Session) -> TUILogResult<()> {
   let shell_path = user
@@ -35,6 +37,7 @@ fn spawn_session(user: &User, session: &Session) -> TUILogResult<()> {
   Ok(())
 }
 
+// the pre-session hooks are run by the caller, before leaving the greeter
 pub fn spawn_shell_session(user: &User, session: &Session) -> TUILogResult<()> {
   let proc_type =
     unsafe { fork().tuilog_err(TUILogError::ShellSessionFailed)? };
@@ -42,11 +45,19 @@ pub fn spawn_shell_session(user: &User, session: &Session) -> TUILogResult<()> {
   match proc_type {
     ForkResult::Parent { child } => {
       waitpid(child, None).tuilog_err(TUILogError::ShellSessionFailed)?;
+      run_post_session_hooks(user, session);
     }
     ForkResult::Child => {
-      set_process_ids(&user)?;
-      set_env(&user)?;
-      spawn_session(&user, &session)?;
+      // the child only returns on failing, it must never carry on as another
+      // greeter
+      let result = set_process_ids(user)
+        .and_then(|_| set_env(user, session))
+        .and_then(|_| spawn_session(user, session));
+      if let Err(error) = result {
+        log_message(error.message());
+      }
+      // skips the exit handlers and the stdio buffers copied from the parent
+      unsafe { libc::_exit(1) };
     }
   };
 
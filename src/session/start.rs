@@ -6,14 +6,18 @@ use std::sync::Arc;
 use users::{get_user_by_name, User};
 
 use crate::error::{TUILogError, TUILogErrorMap, TUILogResult};
-use crate::session::spawn_shell_session;
-use crate::state::{sessions, set_default_options, Session};
+use crate::session::{
+  is_session_allowed, run_pre_session_hooks, spawn_shell_session,
+};
+use crate::state::{pam_services, sessions, set_default_options, Session};
+use crate::tui::{hide_password, PasswordView};
 
-fn auth_user<'a>(
+fn authenticate<'a>(
+  service: &str,
   username: &'a str,
   password: &'a str,
-) -> TUILogResult<(pam::Client<'a, pam::PasswordConv>, User)> {
-  let mut client = pam::Client::with_password("tuilog")
+) -> TUILogResult<pam::Client<'a, pam::PasswordConv>> {
+  let mut client = pam::Client::with_password(service)
     .tuilog_err(TUILogError::AuthenticationFailed)?;
 
   // Preset the login & password we will use for authentication
@@ -24,6 +28,16 @@ fn auth_user<'a>(
     .authenticate()
     .tuilog_err(TUILogError::Unauthorized)?;
 
+  Ok(client)
+}
+
+pub fn auth_user<'a>(
+  service: &str,
+  username: &'a str,
+  password: &'a str,
+) -> TUILogResult<(pam::Client<'a, pam::PasswordConv>, User)> {
+  let mut client = authenticate(service, username, password)?;
+
   let user =
     get_user_by_name(username).tuilog_err(TUILogError::UserNotFound)?;
 
@@ -34,7 +48,9 @@ fn auth_user<'a>(
   Ok((client, user))
 }
 
-pub fn start_session(siv: &mut Cursive) -> TUILogResult<()> {
+fn get_credentials(
+  siv: &mut Cursive,
+) -> TUILogResult<(Arc<String>, Arc<String>)> {
   fn get_view_content(view: &mut EditView) -> Arc<String> {
     view.get_content()
   }
@@ -42,8 +58,42 @@ pub fn start_session(siv: &mut Cursive) -> TUILogResult<()> {
     .call_on_name("username", get_view_content)
     .tuilog_err(TUILogError::AuthenticationFailed)?;
   let password = siv
-    .call_on_name("password", get_view_content)
+    .call_on_name("password", |view: &mut PasswordView| view.get_content())
+    .tuilog_err(TUILogError::AuthenticationFailed)?;
+
+  Ok((username, password))
+}
+
+// power actions are only restricted when a PAM service is set for them
+pub fn authorize_power_action(siv: &mut Cursive) -> TUILogResult<()> {
+  let Some(ref service) = pam_services.power else {
+    return Ok(());
+  };
+
+  let (username, password) = get_credentials(siv)?;
+  hide_password(siv);
+  authenticate(service, &username, &password)?;
+
+  Ok(())
+}
+
+// quits the lock screen once the password of the user is entered
+pub fn unlock_session(siv: &mut Cursive, username: &str) -> TUILogResult<()> {
+  let password = siv
+    .call_on_name("password", |view: &mut PasswordView| view.get_content())
     .tuilog_err(TUILogError::AuthenticationFailed)?;
+  hide_password(siv);
+  authenticate(&pam_services.unlock, username, &password)?;
+  // tells the lock screen it was left by unlocking it
+  siv.set_user_data(true);
+  siv.quit();
+
+  Ok(())
+}
+
+pub fn start_session(siv: &mut Cursive) -> TUILogResult<()> {
+  let (username, password) = get_credentials(siv)?;
+  hide_password(siv);
   let session = siv
     .call_on_name(
       "session",
@@ -57,9 +107,16 @@ pub fn start_session(siv: &mut Cursive) -> TUILogResult<()> {
     .tuilog_err(TUILogError::InvalidSessionOption)?
     .tuilog_err(TUILogError::InvalidSessionOption)?;
 
-  // has to be before auth_user as the cache file is only accessible to root
+  let (pam_client, user) =
+    auth_user(&pam_services.login, &username, &password)?;
+  if !is_session_allowed(session, &user) {
+    return Err(TUILogError::SessionNotAllowed);
+  }
+  // run while the greeter is still up, so it can show their failure
+  run_pre_session_hooks(&user, session)?;
+  // only remembered once logged in, so failed attempts can't change what is
+  // remembered for another user
   set_default_options(username.to_string(), session.name.clone());
-  let (pam_client, user) = auth_user(&username, &password)?;
   siv.quit();
 
   spawn_shell_session(&user, session)?;
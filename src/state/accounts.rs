@@ -0,0 +1,88 @@
+use nix::unistd::User as PasswdEntry;
+use users::os::unix::UserExt;
+use users::{all_users, get_user_groups, User};
+
+use crate::state::user_picker;
+
+pub struct Account {
+  pub username: String,
+  pub full_name: Option<String>,
+}
+
+impl Account {
+  pub fn display_name(&self) -> &str {
+    match self.full_name {
+      Some(ref full_name) => full_name,
+      None => &self.username,
+    }
+  }
+}
+
+// shells that are used to disable logins for an account
+fn has_login_shell(user: &User) -> bool {
+  match user.shell().file_name().and_then(|shell| shell.to_str()) {
+    Some(shell) => !matches!(shell, "nologin" | "false" | ""),
+    None => false,
+  }
+}
+
+pub fn is_in_groups(user: &User, groups: &[String]) -> bool {
+  if groups.is_empty() {
+    return false;
+  }
+
+  match get_user_groups(user.name(), user.primary_group_id()) {
+    Some(user_groups) => user_groups.iter().any(|group| {
+      groups
+        .iter()
+        .any(|name| group.name().to_str() == Some(name.as_str()))
+    }),
+    None => false,
+  }
+}
+
+// the full name is the first comma separated field of the GECOS entry
+fn get_full_name(username: &str) -> Option<String> {
+  let entry = PasswdEntry::from_name(username).ok()??;
+  let gecos = entry.gecos.into_string().ok()?;
+  let full_name = gecos.split(',').next()?.trim();
+
+  if full_name.is_empty() {
+    None
+  } else {
+    Some(full_name.to_string())
+  }
+}
+
+fn is_human_account(user: &User) -> bool {
+  let username = user.name().to_string_lossy();
+
+  if user_picker.exclude.iter().any(|name| *name == username) {
+    return false;
+  }
+  if user_picker.include.iter().any(|name| *name == username) {
+    return true;
+  }
+
+  (user_picker.min_uid..=user_picker.max_uid).contains(&user.uid())
+    && has_login_shell(user)
+    && (user_picker.groups.is_empty()
+      || is_in_groups(user, &user_picker.groups))
+}
+
+pub fn get_accounts() -> Vec<Account> {
+  // SAFETY: no other thread iterates over the passwd database
+  let mut accounts: Vec<Account> = unsafe { all_users() }
+    .filter(is_human_account)
+    .filter_map(|user| user.name().to_str().map(str::to_string))
+    .map(|username| Account {
+      full_name: get_full_name(&username),
+      username,
+    })
+    .collect();
+
+  accounts.sort_by(|a, b| a.username.cmp(&b.username));
+  accounts.dedup_by(|a, b| a.username == b.username);
+
+  accounts
+}
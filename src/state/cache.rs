@@ -1,48 +1,340 @@
+use nix::fcntl::{Flock, FlockArg};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::BTreeMap;
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
 
-use crate::state::{cache_dir, cache_file};
+use crate::state::{cache_dir, cache_file, cache_lock_file, remember};
+use crate::utils::{get_current_tty, get_unix_time};
 
-#[derive(Serialize, Deserialize)]
+// bump this and add a step to `migrate_cache` on changing the cache format
+const CACHE_VERSION: i64 = 1;
+
+#[derive(Serialize, Deserialize, Default)]
 pub struct Cache {
+  #[serde(default)]
+  pub version: i64,
+  #[serde(flatten)]
+  pub global: CacheEntry,
+  // entries scoped to a single tty, the global entry is the fallback
+  #[serde(default)]
+  pub ttys: BTreeMap<String, CacheEntry>,
+  // set for a cache written by a newer version of tuilog, which is never
+  // rewritten as this version can't tell what it would lose
+  #[serde(skip)]
+  read_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CacheEntry {
   pub default: Option<DefaultOptions>,
+  // last session of each user, keyed by username
+  #[serde(default)]
+  pub user_sessions: BTreeMap<String, String>,
+  // fields unknown to this version, kept as is on rewriting the cache
+  #[serde(flatten)]
+  pub extra: toml::Table,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct DefaultOptions {
   pub username: Option<String>,
   pub session_name: Option<String>,
+  // unix time of the login these options were remembered on
+  pub timestamp: Option<u64>,
+  #[serde(flatten)]
+  pub extra: toml::Table,
+}
+
+impl CacheEntry {
+  fn remember(&mut self, username: &str, session_name: &str) {
+    let default_options = self.default.get_or_insert_with(Default::default);
+    default_options.username = remember.username.then(|| username.to_string());
+    default_options.session_name =
+      remember.session.then(|| session_name.to_string());
+    default_options.timestamp = Some(get_unix_time());
+
+    // the sessions are keyed by username, so they are only kept when both
+    // are remembered
+    if remember.username && remember.session {
+      self
+        .user_sessions
+        .insert(username.to_string(), session_name.to_string());
+    } else {
+      self.user_sessions.clear();
+    }
+  }
+
+  // drops the username remembered longer than allowed, along with its session
+  fn forget_expired(&mut self) -> bool {
+    let Some(ref mut default_options) = self.default else {
+      return false;
+    };
+    if !default_options.is_username_expired() {
+      return false;
+    }
+
+    match default_options.username.take() {
+      Some(username) => {
+        self.user_sessions.remove(&username);
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+impl DefaultOptions {
+  fn is_username_expired(&self) -> bool {
+    match remember.username_timeout {
+      Some(timeout) => match self.timestamp {
+        Some(timestamp) => get_unix_time().saturating_sub(timestamp) > timeout,
+        None => true,
+      },
+      None => false,
+    }
+  }
+
+  // drops whatever the config does not allow to be remembered
+  fn forget_private(mut self) -> Self {
+    if !remember.username || self.is_username_expired() {
+      self.username = None;
+    }
+    if !remember.session {
+      self.session_name = None;
+    }
+
+    self
+  }
+}
+
+impl Cache {
+  fn tty_entry(&self) -> Option<&CacheEntry> {
+    get_current_tty().and_then(|tty| self.ttys.get(&tty))
+  }
+
+  // returns whether anything was forgotten
+  fn forget_expired(&mut self) -> bool {
+    let mut forgotten = self.global.forget_expired();
+    for entry in self.ttys.values_mut() {
+      forgotten |= entry.forget_expired();
+    }
+
+    forgotten
+  }
+}
+
+// upgrades a cache written by an older version of tuilog
+fn migrate_cache(cache: &mut toml::Table) {
+  let mut version = cache
+    .get("version")
+    .and_then(toml::Value::as_integer)
+    .unwrap_or(0);
+
+  // version 0 only had the `default` table, which is read as the global
+  // entry as is
+  if version < 1 {
+    version = 1;
+  }
+
+  cache.insert("version".to_string(), toml::Value::Integer(version));
+}
+
+// serializes access to the cache file between the instances on each tty
+fn lock_cache(lock_type: FlockArg) -> Option<Flock<File>> {
+  let lock_file = OpenOptions::new()
+    .create(true)
+    .truncate(false)
+    .write(true)
+    .mode(0o600)
+    .open(&*cache_lock_file)
+    .ok()?;
+
+  Flock::lock(lock_file, lock_type).ok()
+}
+
+fn parse_cache(cache_content: &str) -> Cache {
+  let cache: Option<toml::Table> = toml::from_str(cache_content).ok();
+  // checked before reading the cache into this version's format, which a
+  // newer format may not fit
+  let read_only = cache
+    .as_ref()
+    .and_then(|cache| cache.get("version"))
+    .and_then(toml::Value::as_integer)
+    .is_some_and(|version| version > CACHE_VERSION);
+
+  let cache = cache.and_then(|mut cache| {
+    migrate_cache(&mut cache);
+    toml::Value::Table(cache).try_into().ok()
+  });
+
+  Cache {
+    read_only,
+    ..cache.unwrap_or(Cache {
+      version: CACHE_VERSION,
+      ..Default::default()
+    })
+  }
+}
+
+fn read_cache() -> Cache {
+  parse_cache(&fs::read_to_string(&*cache_file).unwrap_or_default())
+}
+
+// replaces the cache file with a temporary file so that it is never left
+// partially written
+fn write_cache(cache: &Cache) -> std::io::Result<()> {
+  let cache_content = toml::to_string(cache).map_err(std::io::Error::other)?;
+  let temp_cache_file = cache_file.with_extension("toml.tmp");
+
+  let mut file = OpenOptions::new()
+    .create(true)
+    .truncate(true)
+    .write(true)
+    .mode(0o600)
+    .open(&temp_cache_file)?;
+  file.write_all(cache_content.as_bytes())?;
+  file.sync_all()?;
+
+  fs::rename(&temp_cache_file, &*cache_file)?;
+  File::open(&*cache_dir)?.sync_all()
 }
 
 pub fn set_default_options(username: String, session_name: String) {
-  let cache = Cache {
-    default: Some(DefaultOptions {
-      username: Some(username),
-      session_name: Some(session_name),
-    }),
+  if DirBuilder::new()
+    .recursive(true)
+    .mode(0o700)
+    .create(&*cache_dir)
+    .is_err()
+  {
+    return;
+  }
+
+  let Some(_lock) = lock_cache(FlockArg::LockExclusive) else {
+    return;
   };
 
-  if let Err(_) = fs::create_dir_all(&*cache_dir) {
+  let mut cache = read_cache();
+  if cache.read_only {
     return;
   }
 
-  fs::write(&*cache_file, toml::to_string(&cache).unwrap()).ok();
+  cache.forget_expired();
+  cache.global.remember(&username, &session_name);
+  if let Some(tty) = get_current_tty() {
+    cache
+      .ttys
+      .entry(tty)
+      .or_default()
+      .remember(&username, &session_name);
+  }
+
+  write_cache(&cache).ok();
 }
 
 pub fn get_default_options() -> DefaultOptions {
-  let default_options = match fs::read_to_string(&*cache_file) {
-    Ok(cache_content) => match toml::from_str::<Cache>(&cache_content) {
-      Ok(cache) => cache.default,
-      Err(_) => None,
-    },
-    Err(_) => None,
-  };
+  // exclusive as an expired username is removed from the cache right away
+  let _lock = lock_cache(FlockArg::LockExclusive);
+  let mut cache = read_cache();
+  if !cache.read_only && cache.forget_expired() {
+    write_cache(&cache).ok();
+  }
+
+  cache
+    .tty_entry()
+    .and_then(|entry| entry.default.clone())
+    .or(cache.global.default)
+    .unwrap_or_default()
+    .forget_private()
+}
+
+pub fn get_user_session(username: &str) -> Option<String> {
+  if !(remember.username && remember.session) {
+    return None;
+  }
+
+  let _lock = lock_cache(FlockArg::LockShared);
+  let cache = read_cache();
+
+  cache
+    .tty_entry()
+    .and_then(|entry| entry.user_sessions.get(username))
+    .or(cache.global.user_sessions.get(username))
+    .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn migrates_unversioned_cache() {
+    let cache = parse_cache(
+      r#"
+      [default]
+      username = "alice"
+      session_name = "bash"
+      "#,
+    );
+
+    assert_eq!(cache.version, 1);
+    let default_options = cache.global.default.unwrap();
+    assert_eq!(default_options.username.as_deref(), Some("alice"));
+    assert_eq!(default_options.session_name.as_deref(), Some("bash"));
+  }
+
+  #[test]
+  fn keeps_newer_cache_version() {
+    let cache = parse_cache("version = 7");
+    assert_eq!(cache.version, 7);
+    assert!(cache.read_only);
+    assert!(!parse_cache("version = 1").read_only);
+  }
+
+  #[test]
+  fn keeps_newer_cache_read_only_when_its_format_does_not_fit() {
+    let cache = parse_cache(
+      r#"
+      version = 7
+      ttys = "moved elsewhere"
+      "#,
+    );
+    assert!(cache.read_only);
+
+    // an unreadable cache of this version is replaced
+    let cache = parse_cache(r#"ttys = "broken""#);
+    assert_eq!(cache.version, CACHE_VERSION);
+    assert!(!cache.read_only);
+  }
+
+  #[test]
+  fn round_trips_cache_with_unknown_fields() {
+    let cache = parse_cache(
+      r#"
+      version = 1
+      unknown = "kept"
+
+      [default]
+      username = "alice"
+      timestamp = 42
+      color = "red"
+
+      [user_sessions]
+      alice = "bash"
+
+      [ttys.tty2.user_sessions]
+      bob = "sway"
+      "#,
+    );
 
-  match default_options {
-    Some(default_options) => default_options,
-    None => DefaultOptions {
-      username: None,
-      session_name: None,
-    },
+    let cache = parse_cache(&toml::to_string(&cache).unwrap());
+    assert_eq!(cache.version, 1);
+    assert_eq!(cache.global.extra["unknown"].as_str(), Some("kept"));
+    let default_options = cache.global.default.unwrap();
+    assert_eq!(default_options.timestamp, Some(42));
+    assert_eq!(default_options.extra["color"].as_str(), Some("red"));
+    assert_eq!(cache.global.user_sessions["alice"], "bash");
+    assert_eq!(cache.ttys["tty2"].user_sessions["bob"], "sway");
   }
 }
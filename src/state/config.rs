@@ -1,32 +1,237 @@
+use cursive::event::{Event, Key};
 use cursive::theme::BaseColor;
 use gethostname::gethostname;
 use lazy_static::lazy_static;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use crate::utils::{get_current_tty, get_os_release_field};
+
+fn default_title_art_config() -> TitleArt {
+  TitleArt {
+    font: default_title_font(),
+    spacing: default_title_spacing(),
+    plain: false,
+    fallback_font: None,
+  }
+}
+fn default_title_font() -> String {
+  "standard".to_string()
+}
+fn default_title_spacing() -> usize {
+  1
+}
 fn default_cache_dir() -> String {
   "/var/cache/tuilog".to_string()
 }
+fn default_hook_timeout() -> u64 {
+  10
+}
 fn default_ascii_art_config() -> AsciiArt {
   AsciiArt {
     background: None,
     background_art_color: BaseColor::White,
+    background_frame_separator: None,
+    background_fps: default_background_fps(),
+    background_loop: true,
+    background_random: false,
+    background_fit_to_screen: false,
+    background_overrides: Vec::new(),
+    background_align: BackgroundAlign::default(),
+    background_crop: BackgroundCrop::default(),
+    background_small: None,
     error_icon: None,
   }
 }
 fn default_base_color() -> BaseColor {
   BaseColor::White
 }
+fn default_background_fps() -> u32 {
+  2
+}
+fn default_user_picker_config() -> UserPicker {
+  UserPicker {
+    enable: false,
+    min_uid: default_min_uid(),
+    max_uid: default_max_uid(),
+    include: Vec::new(),
+    exclude: Vec::new(),
+    groups: Vec::new(),
+  }
+}
+fn default_remember_config() -> Remember {
+  Remember {
+    username: true,
+    session: true,
+    username_timeout: None,
+  }
+}
+fn default_autologin_config() -> Autologin {
+  Autologin {
+    user: None,
+    session: None,
+    tty: None,
+    only_first_start: false,
+    grace_period: default_autologin_grace_period(),
+  }
+}
+fn default_autologin_grace_period() -> u64 {
+  2
+}
+fn default_pam_services_config() -> PamServices {
+  PamServices {
+    login: default_login_service(),
+    autologin: default_autologin_service(),
+    power: None,
+    unlock: default_login_service(),
+  }
+}
+fn default_login_service() -> String {
+  "tuilog".to_string()
+}
+fn default_autologin_service() -> String {
+  "tuilog-autologin".to_string()
+}
+fn default_idle_config() -> Idle {
+  Idle {
+    timeout: None,
+    blank: false,
+    screensaver: None,
+  }
+}
+fn default_issue_config() -> Issue {
+  Issue {
+    enable: false,
+    path: None,
+    height: default_issue_height(),
+  }
+}
+fn default_issue_height() -> usize {
+  6
+}
+fn default_notice_config() -> Notice {
+  Notice {
+    enable: false,
+    title: default_notice_title(),
+    body: None,
+    accept_text: default_notice_accept_text(),
+    idle_timeout: None,
+  }
+}
+fn default_notice_title() -> String {
+  "NOTICE".to_string()
+}
+fn default_notice_accept_text() -> String {
+  "ACCEPT".to_string()
+}
+fn default_keyboard_config() -> Keyboard {
+  Keyboard {
+    caps_lock_warning: true,
+    num_lock: false,
+  }
+}
+fn default_password_config() -> Password {
+  Password {
+    mask: default_password_mask(),
+    allow_reveal: true,
+    reveal_key: default_reveal_key(),
+  }
+}
+fn default_password_mask() -> String {
+  "*".to_string()
+}
+fn default_reveal_key() -> KeyBinding {
+  KeyBinding::new("F4").unwrap()
+}
+fn default_keybindings_config() -> Keybindings {
+  Keybindings {
+    help: default_help_key(),
+    session: default_session_key(),
+    user: default_user_key(),
+    reboot: default_reboot_key(),
+    shutdown: default_shutdown_key(),
+    hint_bar: true,
+  }
+}
+fn default_help_key() -> Option<KeyBinding> {
+  KeyBinding::new("F1")
+}
+fn default_session_key() -> Option<KeyBinding> {
+  KeyBinding::new("F2")
+}
+fn default_user_key() -> Option<KeyBinding> {
+  KeyBinding::new("F3")
+}
+fn default_reboot_key() -> Option<KeyBinding> {
+  KeyBinding::new("F11")
+}
+fn default_shutdown_key() -> Option<KeyBinding> {
+  KeyBinding::new("F12")
+}
+fn default_true() -> bool {
+  true
+}
+fn default_min_uid() -> u32 {
+  1000
+}
+fn default_max_uid() -> u32 {
+  60000
+}
 
 #[derive(Deserialize)]
 struct Config {
   title: Option<String>,
+  #[serde(default = "default_title_art_config")]
+  title_art: TitleArt,
   #[serde(default = "default_cache_dir")]
   cache_dir: String,
   #[serde(default = "default_ascii_art_config")]
   ascii_art: AsciiArt,
   #[serde(default)]
   sessions: Vec<Session>,
+  #[serde(default = "default_user_picker_config")]
+  user_picker: UserPicker,
+  #[serde(default = "default_remember_config")]
+  remember: Remember,
+  #[serde(default = "default_issue_config")]
+  issue: Issue,
+  #[serde(default = "default_notice_config")]
+  notice: Notice,
+  #[serde(default = "default_keyboard_config")]
+  keyboard: Keyboard,
+  #[serde(default = "default_password_config")]
+  password: Password,
+  #[serde(default = "default_keybindings_config")]
+  keybindings: Keybindings,
+  #[serde(default)]
+  help: Help,
+  // variables set for every session, before the ones of the session
+  #[serde(default)]
+  environment: HashMap<String, String>,
+  #[serde(default)]
+  hooks: Hooks,
+  #[serde(default = "default_autologin_config")]
+  autologin: Autologin,
+  #[serde(default = "default_pam_services_config")]
+  pam: PamServices,
+  #[serde(default = "default_idle_config")]
+  idle: Idle,
+}
+
+#[derive(Deserialize)]
+pub struct TitleArt {
+  // one of "standard", "small" or "default"
+  #[serde(default = "default_title_font")]
+  pub font: String,
+  // columns between the letters
+  #[serde(default = "default_title_spacing")]
+  pub spacing: usize,
+  // show the title as it is instead of as ascii art
+  #[serde(default)]
+  pub plain: bool,
+  // used when the title is too wide for the terminal in the font
+  pub fallback_font: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -37,13 +242,292 @@ struct AsciiArt {
     default = "default_base_color"
   )]
   background_art_color: BaseColor,
+  // a line that splits the background file into animation frames
+  background_frame_separator: Option<String>,
+  #[serde(default = "default_background_fps")]
+  background_fps: u32,
+  #[serde(default = "default_true")]
+  background_loop: bool,
+  // pick a file from the background directory instead of animating it
+  #[serde(default)]
+  background_random: bool,
+  // skip the files larger than the terminal when picking at random
+  #[serde(default)]
+  background_fit_to_screen: bool,
+  #[serde(default)]
+  background_overrides: Vec<BackgroundOverride>,
+  #[serde(default)]
+  background_align: BackgroundAlign,
+  #[serde(default)]
+  background_crop: BackgroundCrop,
+  // drawn instead when the background doesn't fit the terminal
+  background_small: Option<String>,
   error_icon: Option<String>,
 }
 
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundAlign {
+  #[default]
+  Center,
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  BottomRight,
+  Tiled,
+}
+
+// what happens to art larger than the terminal
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundCrop {
+  // keep the part on the side it is aligned to
+  #[default]
+  Align,
+  Hide,
+}
+
+// replaces the background when all of its given conditions match
+#[derive(Deserialize)]
+pub struct BackgroundOverride {
+  background: String,
+  pub tty: Option<String>,
+  // times of the day given as HH:MM, in minutes since midnight, the range may
+  // wrap around midnight
+  #[serde(default, deserialize_with = "deserialize_time_of_day")]
+  pub from: Option<u32>,
+  #[serde(default, deserialize_with = "deserialize_time_of_day")]
+  pub to: Option<u32>,
+  // days of the year given as MM-DD, as (month, day)
+  #[serde(default, deserialize_with = "deserialize_dates")]
+  pub dates: Vec<(u32, u32)>,
+}
+
+impl BackgroundOverride {
+  pub fn background_path(&self) -> PathBuf {
+    resolve_config_path(&self.background)
+  }
+}
+
+#[derive(Deserialize)]
+pub struct UserPicker {
+  #[serde(default)]
+  pub enable: bool,
+  #[serde(default = "default_min_uid")]
+  pub min_uid: u32,
+  #[serde(default = "default_max_uid")]
+  pub max_uid: u32,
+  #[serde(default)]
+  pub include: Vec<String>,
+  #[serde(default)]
+  pub exclude: Vec<String>,
+  #[serde(default)]
+  pub groups: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Remember {
+  #[serde(default = "default_true")]
+  pub username: bool,
+  #[serde(default = "default_true")]
+  pub session: bool,
+  // seconds after which the remembered username is forgotten
+  pub username_timeout: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct Autologin {
+  // autologin is disabled without a user
+  pub user: Option<String>,
+  // defaults to the remembered session of the user, then the first session
+  pub session: Option<String>,
+  // only log in on this tty, any tty if not given
+  pub tty: Option<String>,
+  // only log in on the first start of the greeter since boot
+  #[serde(default)]
+  pub only_first_start: bool,
+  // seconds to wait for a key press that cancels the autologin
+  #[serde(default = "default_autologin_grace_period")]
+  pub grace_period: u64,
+}
+
+#[derive(Deserialize)]
+pub struct PamServices {
+  #[serde(default = "default_login_service")]
+  pub login: String,
+  // the PAM stack of this service shouldn't ask for a password
+  #[serde(default = "default_autologin_service")]
+  pub autologin: String,
+  // shutdown and reboot need the credentials in the form to pass this
+  // service when given
+  pub power: Option<String>,
+  #[serde(default = "default_login_service")]
+  pub unlock: String,
+}
+
+#[derive(Deserialize)]
+pub struct Idle {
+  // seconds without input after which the form is reset, never if not given
+  pub timeout: Option<u64>,
+  // blank the screen until a key is pressed after resetting the form
+  #[serde(default)]
+  pub blank: bool,
+  // shown instead of the blank screen
+  pub screensaver: Option<ScreensaverKind>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreensaverKind {
+  Starfield,
+  Matrix,
+  BouncingLogo,
+  Clock,
+}
+
+#[derive(Deserialize)]
+pub struct Issue {
+  #[serde(default)]
+  pub enable: bool,
+  path: Option<String>,
+  #[serde(default = "default_issue_height")]
+  pub height: usize,
+}
+
+#[derive(Deserialize)]
+pub struct Notice {
+  #[serde(default)]
+  pub enable: bool,
+  #[serde(default = "default_notice_title")]
+  pub title: String,
+  body: Option<String>,
+  #[serde(default = "default_notice_accept_text")]
+  pub accept_text: String,
+  // seconds without input after which the notice is shown again
+  pub idle_timeout: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct Keyboard {
+  #[serde(default = "default_true")]
+  pub caps_lock_warning: bool,
+  // turn on num lock when the greeter starts
+  #[serde(default)]
+  pub num_lock: bool,
+}
+
+#[derive(Deserialize)]
+pub struct Password {
+  // shown in place of each typed character, nothing is shown if empty
+  #[serde(default = "default_password_mask")]
+  pub mask: String,
+  #[serde(default = "default_true")]
+  pub allow_reveal: bool,
+  #[serde(
+    deserialize_with = "deserialize_key_binding",
+    default = "default_reveal_key"
+  )]
+  pub reveal_key: KeyBinding,
+}
+
+// a key given as "F1", "Ctrl+r", "Alt+x", "Esc" or a single character
+#[derive(Clone)]
+pub struct KeyBinding {
+  pub name: String,
+  pub event: Event,
+}
+
+// an empty key disables the binding
+#[derive(Deserialize)]
+pub struct Keybindings {
+  #[serde(
+    deserialize_with = "deserialize_optional_key_binding",
+    default = "default_help_key"
+  )]
+  pub help: Option<KeyBinding>,
+  #[serde(
+    deserialize_with = "deserialize_optional_key_binding",
+    default = "default_session_key"
+  )]
+  pub session: Option<KeyBinding>,
+  #[serde(
+    deserialize_with = "deserialize_optional_key_binding",
+    default = "default_user_key"
+  )]
+  pub user: Option<KeyBinding>,
+  #[serde(
+    deserialize_with = "deserialize_optional_key_binding",
+    default = "default_reboot_key"
+  )]
+  pub reboot: Option<KeyBinding>,
+  #[serde(
+    deserialize_with = "deserialize_optional_key_binding",
+    default = "default_shutdown_key"
+  )]
+  pub shutdown: Option<KeyBinding>,
+  #[serde(default = "default_true")]
+  pub hint_bar: bool,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Help {
+  // who to contact for help, shown in the help dialog
+  pub contact: Option<String>,
+}
+
+#[derive(Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HookUser {
+  #[default]
+  Root,
+  User,
+}
+
+#[derive(Deserialize)]
+pub struct Hook {
+  // run with /bin/sh -c, with USER, TTY and SESSION in the environment
+  pub exec: String,
+  #[serde(default)]
+  pub run_as: HookUser,
+  // seconds after which the hook is killed
+  #[serde(default = "default_hook_timeout")]
+  pub timeout: u64,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Hooks {
+  #[serde(default)]
+  pub pre_session: Vec<Hook>,
+  #[serde(default)]
+  pub post_session: Vec<Hook>,
+  // abort the login when a pre session hook fails or times out
+  #[serde(default)]
+  pub fail_closed: bool,
+}
+
 #[derive(Deserialize)]
 pub struct Session {
   pub name: String,
   pub exec: String,
+  pub description: Option<String>,
+  // small ascii art shown next to the description
+  pub icon: Option<String>,
+  // usually the Comment of the .desktop file of the session
+  pub comment: Option<String>,
+  // the session is open to everyone when both allow lists are empty
+  #[serde(default)]
+  pub allowed_users: Vec<String>,
+  #[serde(default)]
+  pub allowed_groups: Vec<String>,
+  #[serde(default)]
+  pub denied_groups: Vec<String>,
+  // $HOME and $USER are expanded in the values and in cwd
+  #[serde(default)]
+  pub env: HashMap<String, String>,
+  #[serde(default)]
+  pub unset_env: Vec<String>,
+  // defaults to the home directory of the user
+  pub cwd: Option<String>,
 }
 
 fn deserialize_base_color<'de, D>(
@@ -68,6 +552,181 @@ where
   }
 }
 
+// reads HH:MM as minutes since midnight, the hour may be a single digit
+pub fn parse_time_of_day(time: &str) -> Option<u32> {
+  let (hours, minutes) = time.split_once(':')?;
+  if !(1..=2).contains(&hours.len()) || minutes.len() != 2 {
+    return None;
+  }
+
+  let hours: u32 = hours.parse().ok()?;
+  let minutes: u32 = minutes.parse().ok()?;
+  (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+// reads MM-DD as (month, day), each may be a single digit
+pub fn parse_date(date: &str) -> Option<(u32, u32)> {
+  let (month, day) = date.split_once('-')?;
+  if !(1..=2).contains(&month.len()) || !(1..=2).contains(&day.len()) {
+    return None;
+  }
+
+  let month: u32 = month.parse().ok()?;
+  let day: u32 = day.parse().ok()?;
+  ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some((month, day))
+}
+
+fn deserialize_time_of_day<'de, D>(
+  deserializer: D,
+) -> Result<Option<u32>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let time = String::deserialize(deserializer)?;
+  parse_time_of_day(&time).map(Some).ok_or_else(|| {
+    serde::de::Error::custom(format!("Invalid time, expected HH:MM: {}", time))
+  })
+}
+
+fn deserialize_dates<'de, D>(
+  deserializer: D,
+) -> Result<Vec<(u32, u32)>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  Vec::<String>::deserialize(deserializer)?
+    .iter()
+    .map(|date| {
+      parse_date(date).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+          "Invalid date, expected MM-DD: {}",
+          date
+        ))
+      })
+    })
+    .collect()
+}
+
+fn get_os_name() -> String {
+  get_os_release_field("NAME").unwrap_or_else(|| "Linux".to_string())
+}
+
+// falls back to the name of the os when there is no hostname
+fn get_hostname() -> String {
+  let hostname = gethostname().to_string_lossy().trim().to_string();
+  if hostname.is_empty() {
+    get_os_name()
+  } else {
+    hostname
+  }
+}
+
+// expands {hostname}, {os_name} and {tty} in the title
+fn expand_title_template(template: &str) -> String {
+  let mut expanded = template.to_string();
+
+  if expanded.contains("{hostname}") {
+    expanded = expanded.replace("{hostname}", &get_hostname());
+  }
+  if expanded.contains("{os_name}") {
+    expanded = expanded.replace("{os_name}", &get_os_name());
+  }
+  if expanded.contains("{tty}") {
+    expanded =
+      expanded.replace("{tty}", &get_current_tty().unwrap_or_default());
+  }
+
+  expanded
+}
+
+// relative paths are resolved from the config directory
+fn resolve_config_path(path: &str) -> PathBuf {
+  let path = Path::new(path);
+
+  if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    (*base_path).join(path)
+  }
+}
+
+fn parse_key(key: &str) -> Option<Event> {
+  let mut chars = key.chars();
+  if let (Some(c), None) = (chars.next(), chars.next()) {
+    return Some(Event::Char(c));
+  }
+
+  if let Some(c) = key.strip_prefix("Ctrl+") {
+    return parse_key(c).and_then(|event| match event {
+      Event::Char(c) => Some(Event::CtrlChar(c.to_ascii_lowercase())),
+      Event::Key(key) => Some(Event::Ctrl(key)),
+      _ => None,
+    });
+  }
+  if let Some(c) = key.strip_prefix("Alt+") {
+    return parse_key(c).and_then(|event| match event {
+      Event::Char(c) => Some(Event::AltChar(c)),
+      Event::Key(key) => Some(Event::Alt(key)),
+      _ => None,
+    });
+  }
+
+  let key = match key {
+    "Esc" => Key::Esc,
+    "Tab" => Key::Tab,
+    "Enter" => Key::Enter,
+    "Backspace" => Key::Backspace,
+    "Insert" => Key::Ins,
+    "Delete" => Key::Del,
+    "Home" => Key::Home,
+    "End" => Key::End,
+    "PageUp" => Key::PageUp,
+    "PageDown" => Key::PageDown,
+    function_key => match function_key.strip_prefix('F')?.parse() {
+      Ok(n @ 1..=12) => Key::from_f(n),
+      _ => return None,
+    },
+  };
+
+  Some(Event::Key(key))
+}
+
+impl KeyBinding {
+  fn new(name: &str) -> Option<KeyBinding> {
+    Some(KeyBinding {
+      name: name.to_string(),
+      event: parse_key(name)?,
+    })
+  }
+}
+
+fn deserialize_key_binding<'de, D>(
+  deserializer: D,
+) -> Result<KeyBinding, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let key = String::deserialize(deserializer)?;
+  KeyBinding::new(&key)
+    .ok_or_else(|| serde::de::Error::custom(format!("Invalid key: {}", key)))
+}
+
+fn deserialize_optional_key_binding<'de, D>(
+  deserializer: D,
+) -> Result<Option<KeyBinding>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let key = String::deserialize(deserializer)?;
+  if key.is_empty() {
+    return Ok(None);
+  }
+
+  KeyBinding::new(&key)
+    .map(Some)
+    .ok_or_else(|| serde::de::Error::custom(format!("Invalid key: {}", key)))
+}
+
 lazy_static! {
     static ref base_path: PathBuf =
         PathBuf::from(
@@ -86,20 +745,36 @@ lazy_static! {
             .unwrap_or(
                 Config {
                     title: None,
+                    title_art: default_title_art_config(),
                     cache_dir: default_cache_dir(),
                     ascii_art: default_ascii_art_config(),
                     sessions: Vec::new(),
+                    user_picker: default_user_picker_config(),
+                    remember: default_remember_config(),
+                    issue: default_issue_config(),
+                    notice: default_notice_config(),
+                    keyboard: default_keyboard_config(),
+                    password: default_password_config(),
+                    keybindings: default_keybindings_config(),
+                    help: Help::default(),
+                    environment: HashMap::new(),
+                    hooks: Hooks::default(),
+                    autologin: default_autologin_config(),
+                    pam: default_pam_services_config(),
+                    idle: default_idle_config(),
                 }
             )
     };
 
-    /* TODO: Add the edge case when the title
-    isn't given and hostname can't be found */
+    // the hostname is shown when no title is given
     pub static ref title: String = {
-        match config.title {
-            Some(ref login_title) => login_title.to_string(),
-            None => gethostname().into_string().unwrap(),
-        }
+        expand_title_template(
+            config.title.as_deref().unwrap_or("{hostname}")
+        )
+    };
+
+    pub static ref title_art: &'static TitleArt = {
+        &config.title_art
     };
 
     pub static ref cache_dir: PathBuf = {
@@ -110,46 +785,230 @@ lazy_static! {
         &config.sessions
     };
 
+    pub static ref user_picker: &'static UserPicker = {
+        &config.user_picker
+    };
+
+    pub static ref remember: &'static Remember = {
+        &config.remember
+    };
+
     pub static ref cache_file: PathBuf = (*cache_dir)
         .join("cache.toml");
 
+    pub static ref cache_lock_file: PathBuf = (*cache_dir)
+        .join("cache.lock");
+
     pub static ref background_ascii_art_path: Option<PathBuf> = {
-        match config.ascii_art.background {
-            Some(ref background) => {
-                let background_path = Path::new(background);
-
-                Some(
-                    if background_path.is_absolute() {
-                        background_path.to_path_buf()
-                    } else {
-                        (*base_path)
-                            .join(background_path)
-                    }
-                )
-            },
-            None => None,
-        }
+        config.ascii_art.background
+            .as_deref()
+            .map(resolve_config_path)
     };
 
     pub static ref background_ascii_art_color: BaseColor = {
         config.ascii_art.background_art_color
     };
 
+    pub static ref background_ascii_art_frame_separator: Option<String> = {
+        config.ascii_art.background_frame_separator.clone()
+    };
+
+    pub static ref background_ascii_art_fps: u32 = {
+        config.ascii_art.background_fps.max(1)
+    };
+
+    pub static ref background_ascii_art_loop: bool = {
+        config.ascii_art.background_loop
+    };
+
+    pub static ref background_ascii_art_random: bool = {
+        config.ascii_art.background_random
+    };
+
+    pub static ref background_ascii_art_fit_to_screen: bool = {
+        config.ascii_art.background_fit_to_screen
+    };
+
+    pub static ref background_ascii_art_align: BackgroundAlign = {
+        config.ascii_art.background_align
+    };
+
+    pub static ref background_ascii_art_crop: BackgroundCrop = {
+        config.ascii_art.background_crop
+    };
+
+    pub static ref background_ascii_art_small_path: Option<PathBuf> = {
+        config.ascii_art.background_small
+            .as_deref()
+            .map(resolve_config_path)
+    };
+
+    pub static ref background_ascii_art_overrides: &'static Vec<BackgroundOverride> = {
+        &config.ascii_art.background_overrides
+    };
+
     pub static ref error_icon_ascii_art_path: Option<PathBuf> = {
-        match config.ascii_art.error_icon {
-            Some(ref error_icon) => {
-                let error_icon_path = Path::new(error_icon);
-
-                Some(
-                    if error_icon_path.is_absolute() {
-                        error_icon_path.to_path_buf()
-                    } else {
-                        (*base_path)
-                            .join(error_icon_path)
-                    }
-                )
-            },
-            None => None,
-        }
+        config.ascii_art.error_icon
+            .as_deref()
+            .map(resolve_config_path)
+    };
+
+    pub static ref issue_config: &'static Issue = {
+        &config.issue
+    };
+
+    pub static ref issue_path: Option<PathBuf> = {
+        config.issue.path
+            .as_deref()
+            .map(resolve_config_path)
+    };
+
+    pub static ref notice_config: &'static Notice = {
+        &config.notice
+    };
+
+    pub static ref notice_body_path: Option<PathBuf> = {
+        config.notice.body
+            .as_deref()
+            .map(resolve_config_path)
+    };
+
+    pub static ref keyboard_config: &'static Keyboard = {
+        &config.keyboard
+    };
+
+    pub static ref password_config: &'static Password = {
+        &config.password
     };
+
+    pub static ref keybindings: &'static Keybindings = {
+        &config.keybindings
+    };
+
+    pub static ref help_config: &'static Help = {
+        &config.help
+    };
+
+    pub static ref environment: &'static HashMap<String, String> = {
+        &config.environment
+    };
+
+    pub static ref hooks: &'static Hooks = {
+        &config.hooks
+    };
+
+    pub static ref autologin_config: &'static Autologin = {
+        &config.autologin
+    };
+
+    pub static ref pam_services: &'static PamServices = {
+        &config.pam
+    };
+
+    pub static ref idle_config: &'static Idle = {
+        &config.idle
+    };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_single_characters() {
+    assert_eq!(parse_key("a"), Some(Event::Char('a')));
+    assert_eq!(parse_key("?"), Some(Event::Char('?')));
+  }
+
+  #[test]
+  fn parses_named_and_function_keys() {
+    assert_eq!(parse_key("Esc"), Some(Event::Key(Key::Esc)));
+    assert_eq!(parse_key("PageDown"), Some(Event::Key(Key::PageDown)));
+    assert_eq!(parse_key("F1"), Some(Event::Key(Key::F1)));
+    assert_eq!(parse_key("F12"), Some(Event::Key(Key::F12)));
+  }
+
+  #[test]
+  fn parses_modifiers() {
+    assert_eq!(parse_key("Ctrl+R"), Some(Event::CtrlChar('r')));
+    assert_eq!(parse_key("Ctrl+F5"), Some(Event::Ctrl(Key::F5)));
+    assert_eq!(parse_key("Alt+x"), Some(Event::AltChar('x')));
+    assert_eq!(parse_key("Alt+Enter"), Some(Event::Alt(Key::Enter)));
+  }
+
+  #[test]
+  fn rejects_unknown_keys() {
+    assert_eq!(parse_key(""), None);
+    assert_eq!(parse_key("F0"), None);
+    assert_eq!(parse_key("F13"), None);
+    assert_eq!(parse_key("Super+a"), None);
+    assert_eq!(parse_key("Ctrl+Alt+a"), None);
+  }
+
+  #[test]
+  fn parses_times_of_day() {
+    assert_eq!(parse_time_of_day("00:00"), Some(0));
+    assert_eq!(parse_time_of_day("06:00"), Some(360));
+    assert_eq!(parse_time_of_day("6:00"), Some(360));
+    assert_eq!(parse_time_of_day("23:59"), Some(1439));
+  }
+
+  #[test]
+  fn rejects_invalid_times_of_day() {
+    assert_eq!(parse_time_of_day("24:00"), None);
+    assert_eq!(parse_time_of_day("12:60"), None);
+    assert_eq!(parse_time_of_day("12:5"), None);
+    assert_eq!(parse_time_of_day("12"), None);
+    assert_eq!(parse_time_of_day("12:00:00"), None);
+    assert_eq!(parse_time_of_day("ab:cd"), None);
+    assert_eq!(parse_time_of_day("-1:00"), None);
+  }
+
+  #[test]
+  fn parses_dates() {
+    assert_eq!(parse_date("12-24"), Some((12, 24)));
+    assert_eq!(parse_date("01-05"), Some((1, 5)));
+    assert_eq!(parse_date("1-5"), Some((1, 5)));
+    assert_eq!(parse_date("13-01"), None);
+    assert_eq!(parse_date("00-10"), None);
+    assert_eq!(parse_date("12-32"), None);
+    assert_eq!(parse_date("12/24"), None);
+    assert_eq!(parse_date("2024-12-24"), None);
+  }
+
+  #[test]
+  fn deserializes_background_overrides() {
+    let background_override: BackgroundOverride = toml::from_str(
+      r#"
+      background = "night"
+      from = "20:00"
+      to = "6:00"
+      dates = ["12-24", "12-25"]
+      "#,
+    )
+    .unwrap();
+    assert_eq!(background_override.from, Some(1200));
+    assert_eq!(background_override.to, Some(360));
+    assert_eq!(background_override.dates, vec![(12, 24), (12, 25)]);
+
+    let background_override: BackgroundOverride =
+      toml::from_str(r#"background = "night""#).unwrap();
+    assert_eq!(background_override.from, None);
+    assert!(background_override.dates.is_empty());
+
+    assert!(toml::from_str::<BackgroundOverride>(
+      r#"
+      background = "night"
+      from = "25:00"
+      "#,
+    )
+    .is_err());
+    assert!(toml::from_str::<BackgroundOverride>(
+      r#"
+      background = "night"
+      dates = ["24-12"]
+      "#,
+    )
+    .is_err());
+  }
 }
@@ -0,0 +1,235 @@
+use nix::ifaddrs::getifaddrs;
+use nix::libc;
+use nix::net::if_::InterfaceFlags;
+use nix::sys::utsname::{uname, UtsName};
+use std::ffi::OsStr;
+use std::fs;
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::str::Chars;
+
+use crate::state::{issue_config, issue_path};
+use crate::utils::{format_local_time, get_current_tty, get_os_release_field};
+
+// the address of the given interface, or of the first one that is up
+fn get_ip_address(interface: Option<&str>, ipv6: bool) -> String {
+  let Ok(interface_addresses) = getifaddrs() else {
+    return String::new();
+  };
+
+  interface_addresses
+    .filter(|address| match interface {
+      Some(interface) => address.interface_name == interface,
+      None => {
+        address.flags.contains(InterfaceFlags::IFF_UP)
+          && !address.flags.contains(InterfaceFlags::IFF_LOOPBACK)
+      }
+    })
+    .find_map(|address| {
+      let address = address.address?;
+      if ipv6 {
+        address
+          .as_sockaddr_in6()
+          .map(|address| address.ip().to_string())
+      } else {
+        address
+          .as_sockaddr_in()
+          .map(|address| address.ip().to_string())
+      }
+    })
+    .unwrap_or_default()
+}
+
+fn count_logged_in_users() -> usize {
+  let mut count = 0;
+
+  unsafe {
+    libc::setutxent();
+    loop {
+      let entry = libc::getutxent();
+      if entry.is_null() {
+        break;
+      }
+      if (*entry).ut_type == libc::USER_PROCESS {
+        count += 1;
+      }
+    }
+    libc::endutxent();
+  }
+
+  count
+}
+
+// reads the `{argument}` following escapes like \4{eth0}
+fn take_escape_argument(chars: &mut Peekable<Chars>) -> Option<String> {
+  if chars.peek() != Some(&'{') {
+    return None;
+  }
+  chars.next();
+
+  let mut argument = String::new();
+  for c in chars.by_ref() {
+    if c == '}' {
+      break;
+    }
+    argument.push(c);
+  }
+
+  Some(argument)
+}
+
+// expands the escape sequences understood by agetty(8)
+fn expand_issue_escapes(issue: &str) -> String {
+  let system = uname().ok();
+  let system_field = |field: fn(&UtsName) -> &OsStr| match system {
+    Some(ref system) => field(system).to_string_lossy().to_string(),
+    None => String::new(),
+  };
+
+  let mut expanded = String::with_capacity(issue.len());
+  let mut chars = issue.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      expanded.push(c);
+      continue;
+    }
+
+    let Some(escape) = chars.next() else {
+      expanded.push('\\');
+      break;
+    };
+
+    let expansion = match escape {
+      '4' | '6' => {
+        let interface = take_escape_argument(&mut chars);
+        get_ip_address(interface.as_deref(), escape == '6')
+      }
+      'd' => format_local_time("%a %b %e %Y"),
+      't' => format_local_time("%H:%M:%S"),
+      'l' => get_current_tty().unwrap_or_default(),
+      'm' => system_field(UtsName::machine),
+      'n' => system_field(UtsName::nodename),
+      'o' | 'O' => system_field(UtsName::domainname),
+      'r' => system_field(UtsName::release),
+      's' => system_field(UtsName::sysname),
+      'v' => system_field(UtsName::version),
+      'S' => get_os_release_field("PRETTY_NAME")
+        .unwrap_or_else(|| system_field(UtsName::sysname)),
+      'u' => count_logged_in_users().to_string(),
+      'U' => match count_logged_in_users() {
+        1 => "1 user".to_string(),
+        users => format!("{} users", users),
+      },
+      // the baud rate and escape characters have no meaning in the greeter
+      'b' | 'e' => {
+        take_escape_argument(&mut chars);
+        String::new()
+      }
+      c => c.to_string(),
+    };
+
+    expanded.push_str(&expansion);
+  }
+
+  expanded
+}
+
+fn get_issue_paths() -> Vec<PathBuf> {
+  if let Some(ref path) = *issue_path {
+    return vec![path.clone()];
+  }
+
+  let mut paths = vec![PathBuf::from("/etc/issue")];
+  if let Ok(entries) = fs::read_dir("/etc/issue.d") {
+    let mut issue_d_paths: Vec<PathBuf> = entries
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| path.extension().is_some_and(|ext| ext == "issue"))
+      .collect();
+    issue_d_paths.sort();
+    paths.extend(issue_d_paths);
+  }
+
+  paths
+}
+
+// keeps each file on its own lines, even when the one before it doesn't end
+// with a newline
+fn join_issue_files(files: impl Iterator<Item = String>) -> String {
+  let mut issue = String::new();
+
+  for file in files {
+    if !issue.is_empty() && !issue.ends_with('\n') {
+      issue.push('\n');
+    }
+    issue.push_str(&file);
+  }
+
+  issue
+}
+
+pub fn get_issue() -> Option<String> {
+  if !issue_config.enable {
+    return None;
+  }
+
+  let issue = join_issue_files(
+    get_issue_paths()
+      .iter()
+      .filter_map(|path| fs::read_to_string(path).ok()),
+  );
+  let issue = expand_issue_escapes(&issue);
+  let issue = issue.trim_end();
+
+  if issue.is_empty() {
+    None
+  } else {
+    Some(issue.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn joins_issue_files_on_separate_lines() {
+    let files = [
+      "first".to_string(),
+      "second\n".to_string(),
+      "third".to_string(),
+    ];
+    assert_eq!(join_issue_files(files.into_iter()), "first\nsecond\nthird");
+  }
+
+  #[test]
+  fn expands_escapes() {
+    assert_eq!(expand_issue_escapes("a\\\\b"), "a\\b");
+    assert_eq!(expand_issue_escapes("\\b{9600}\\e{}x"), "x");
+    assert_eq!(expand_issue_escapes("\\z"), "z");
+    assert_eq!(expand_issue_escapes("trailing\\"), "trailing\\");
+  }
+
+  #[test]
+  fn expands_system_fields() {
+    let system = uname().unwrap();
+    assert_eq!(
+      expand_issue_escapes("\\s \\r"),
+      format!(
+        "{} {}",
+        system.sysname().to_string_lossy(),
+        system.release().to_string_lossy()
+      )
+    );
+  }
+
+  #[test]
+  fn takes_escape_argument() {
+    let mut chars = "{eth0}rest".chars().peekable();
+    assert_eq!(take_escape_argument(&mut chars).as_deref(), Some("eth0"));
+    assert_eq!(chars.collect::<String>(), "rest");
+
+    let mut chars = "rest".chars().peekable();
+    assert_eq!(take_escape_argument(&mut chars), None);
+  }
+}
@@ -1,5 +1,9 @@
+mod accounts;
 mod cache;
 mod config;
+mod issue;
 
+pub use accounts::*;
 pub use cache::*;
 pub use config::*;
+pub use issue::*;
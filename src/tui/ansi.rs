@@ -0,0 +1,219 @@
+use cursive::theme::{BaseColor, Color};
+use std::iter::Peekable;
+use std::str::Chars;
+
+// the colors left as None are the defaults of the art
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AnsiStyle {
+  pub foreground: Option<Color>,
+  pub background: Option<Color>,
+  pub bold: bool,
+}
+
+pub struct AnsiSpan {
+  pub style: AnsiStyle,
+  pub text: String,
+}
+
+// reads the color of a 38 or 48 parameter, as 5;n or 2;r;g;b, None when a
+// value is missing or doesn't fit in a byte
+fn parse_extended_color(
+  params: &mut impl Iterator<Item = u16>,
+) -> Option<Color> {
+  match params.next()? {
+    5 => u8::try_from(params.next()?).ok().map(Color::from_256colors),
+    2 => {
+      // all three are taken so a bad one isn't read as another parameter
+      let (r, g, b) = (params.next()?, params.next()?, params.next()?);
+      Some(Color::Rgb(
+        u8::try_from(r).ok()?,
+        u8::try_from(g).ok()?,
+        u8::try_from(b).ok()?,
+      ))
+    }
+    _ => None,
+  }
+}
+
+// applies a select graphic rendition (SGR) sequence like \x1b[1;31m
+fn apply_sgr(style: &mut AnsiStyle, params: &str) {
+  let mut params = params
+    .split(';')
+    .map(|param| param.parse::<u16>().unwrap_or(0));
+
+  while let Some(param) = params.next() {
+    match param {
+      0 => *style = AnsiStyle::default(),
+      1 => style.bold = true,
+      22 => style.bold = false,
+      30..=37 => {
+        style.foreground = Some(Color::Dark(BaseColor::from(param as u8 - 30)))
+      }
+      90..=97 => {
+        style.foreground = Some(Color::Light(BaseColor::from(param as u8 - 90)))
+      }
+      40..=47 => {
+        style.background = Some(Color::Dark(BaseColor::from(param as u8 - 40)))
+      }
+      100..=107 => {
+        style.background =
+          Some(Color::Light(BaseColor::from(param as u8 - 100)))
+      }
+      // invalid colors are ignored, keeping the current one
+      38 => {
+        if let Some(color) = parse_extended_color(&mut params) {
+          style.foreground = Some(color);
+        }
+      }
+      48 => {
+        if let Some(color) = parse_extended_color(&mut params) {
+          style.background = Some(color);
+        }
+      }
+      39 => style.foreground = None,
+      49 => style.background = None,
+      _ => {}
+    }
+  }
+}
+
+// skips an escape sequence, returning the parameters if it is an SGR one
+fn take_escape_sequence(chars: &mut Peekable<Chars>) -> Option<String> {
+  if chars.next_if_eq(&'[').is_none() {
+    // two character escapes like \x1b7
+    chars.next();
+    return None;
+  }
+
+  let mut params = String::new();
+  for c in chars.by_ref() {
+    // the final byte of a control sequence
+    if ('@'..='~').contains(&c) {
+      return (c == 'm').then_some(params);
+    }
+    params.push(c);
+  }
+
+  None
+}
+
+// splits the text into lines of spans sharing a style, styles carry over
+// to the next line like on a terminal
+pub fn parse_ansi_text(text: &str) -> Vec<Vec<AnsiSpan>> {
+  let mut style = AnsiStyle::default();
+
+  text
+    .lines()
+    .map(|line| {
+      let mut spans: Vec<AnsiSpan> = Vec::new();
+      let mut chars = line.chars().peekable();
+
+      while let Some(c) = chars.next() {
+        if c == '\x1b' {
+          if let Some(params) = take_escape_sequence(&mut chars) {
+            apply_sgr(&mut style, &params);
+          }
+          continue;
+        }
+
+        match spans.last_mut() {
+          Some(span) if span.style == style => span.text.push(c),
+          _ => spans.push(AnsiSpan {
+            style,
+            text: c.to_string(),
+          }),
+        }
+      }
+
+      spans
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sgr(params: &str) -> AnsiStyle {
+    let mut style = AnsiStyle::default();
+    apply_sgr(&mut style, params);
+    style
+  }
+
+  #[test]
+  fn applies_basic_colors_and_bold() {
+    let style = sgr("1;31;44");
+    assert!(style.bold);
+    assert_eq!(style.foreground, Some(Color::Dark(BaseColor::Red)));
+    assert_eq!(style.background, Some(Color::Dark(BaseColor::Blue)));
+
+    let style = sgr("92;107");
+    assert_eq!(style.foreground, Some(Color::Light(BaseColor::Green)));
+    assert_eq!(style.background, Some(Color::Light(BaseColor::White)));
+  }
+
+  #[test]
+  fn applies_extended_colors() {
+    assert_eq!(sgr("38;5;196").foreground, Some(Color::from_256colors(196)));
+    assert_eq!(sgr("48;2;1;2;3").background, Some(Color::Rgb(1, 2, 3)));
+    // incomplete colors are left out
+    assert_eq!(sgr("38;2;1").foreground, None);
+  }
+
+  #[test]
+  fn ignores_extended_colors_out_of_range() {
+    assert_eq!(sgr("38;5;300").foreground, None);
+    assert_eq!(sgr("48;2;1;256;3").background, None);
+
+    // the current color is kept and the rest of the sequence still applies
+    let style = sgr("31;38;2;300;0;0;1");
+    assert_eq!(style.foreground, Some(Color::Dark(BaseColor::Red)));
+    assert!(style.bold);
+  }
+
+  #[test]
+  fn resets_styles() {
+    let mut style = sgr("1;31;41");
+    apply_sgr(&mut style, "22;39");
+    assert!(!style.bold);
+    assert_eq!(style.foreground, None);
+    assert_eq!(style.background, Some(Color::Dark(BaseColor::Red)));
+
+    apply_sgr(&mut style, "0");
+    assert_eq!(style, AnsiStyle::default());
+    // an empty sequence is a reset as well
+    assert_eq!(sgr(""), AnsiStyle::default());
+  }
+
+  #[test]
+  fn parses_spans_and_carries_styles_over_lines() {
+    let lines = parse_ansi_text("a\x1b[31mbc\nd\x1b[0me");
+    assert_eq!(lines.len(), 2);
+
+    let red = sgr("31");
+    let texts: Vec<Vec<(&str, AnsiStyle)>> = lines
+      .iter()
+      .map(|line| {
+        line
+          .iter()
+          .map(|span| (span.text.as_str(), span.style))
+          .collect()
+      })
+      .collect();
+    assert_eq!(
+      texts,
+      vec![
+        vec![("a", AnsiStyle::default()), ("bc", red)],
+        vec![("d", red), ("e", AnsiStyle::default())],
+      ]
+    );
+  }
+
+  #[test]
+  fn skips_other_escape_sequences() {
+    let lines = parse_ansi_text("a\x1b[2Jb\x1b7c");
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].len(), 1);
+    assert_eq!(lines[0][0].text, "abc");
+  }
+}
@@ -1,61 +1,583 @@
-use cursive::theme::{Color, ColorStyle, ColorType, PaletteColor};
+use cursive::theme::{Color, ColorStyle, ColorType, Effect, PaletteColor};
 use cursive::traits::*;
 use cursive::views::{Canvas, Layer, StackView};
-use cursive::Printer;
+use cursive::{Cursive, Printer, Vec2};
+use lazy_static::lazy_static;
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::error::{TUILogError, TUILogResult};
-use crate::state::{background_ascii_art_color, background_ascii_art_path};
-use crate::tui::get_base_theme_ref;
+use crate::state::{
+  background_ascii_art_align, background_ascii_art_color,
+  background_ascii_art_crop, background_ascii_art_fit_to_screen,
+  background_ascii_art_fps, background_ascii_art_frame_separator,
+  background_ascii_art_loop, background_ascii_art_overrides,
+  background_ascii_art_path, background_ascii_art_random,
+  background_ascii_art_small_path, parse_date, parse_time_of_day,
+  BackgroundAlign, BackgroundCrop, BackgroundOverride,
+};
+use crate::tui::{get_base_theme_ref, parse_ansi_text, set_min_fps, AnsiSpan};
+use crate::utils::{
+  format_local_time, get_current_tty, get_terminal_size, Random,
+};
 
-pub fn draw_background_ascii_art(stack: &mut StackView) -> TUILogResult<()> {
-  match &*background_ascii_art_path {
-    Some(ref path) => match fs::read_to_string(path) {
-      Ok(ascii_art) => {
-        let background_color =
-          get_base_theme_ref().palette[PaletteColor::Background];
-        // Create a Canvas to render ASCII art
-        let ascii_view = Canvas::new(ascii_art.to_string()).with_draw(
-          move |ascii_art, printer: &Printer| {
-            let lines: Vec<&str> = ascii_art.lines().collect();
-            let art_height = lines.len();
-            let art_width = lines
-              .iter()
-              .map(|line| line.chars().count())
-              .max()
-              .unwrap_or(0);
-
-            let screen_height = printer.size.y;
-            let screen_width = printer.size.x;
-
-            // Calculate centered position
-            let start_y = (screen_height.saturating_sub(art_height)) / 2;
-            let start_x = (screen_width.saturating_sub(art_width)) / 2;
-
-            for (i, line) in lines.iter().enumerate() {
-              printer.with_color(
-                ColorStyle::new(
-                  ColorType::Color(Color::Dark(
-                    background_ascii_art_color.clone(),
-                  )),
-                  ColorType::Color(background_color),
-                ),
-                |printer| {
-                  printer.print((start_x, start_y + i), line);
-                },
-              )
-            }
-          },
-        );
-
-        // Add the ASCII art as the background
-        stack.add_fullscreen_layer(Layer::new(ascii_view.full_screen()));
+// the overrides take effect within this long of their time coming
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+lazy_static! {
+  // picked on each start of the greeter, and again once an override starts
+  // or ends
+  static ref background_path: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+// lines of spans, plain text is a single span per line in the default style
+type ArtFrame = Vec<Vec<AnsiSpan>>;
+
+// the frames with the width and height taken by the largest one
+struct ArtVariant {
+  frames: Vec<ArtFrame>,
+  size: Vec2,
+}
+
+impl ArtVariant {
+  fn new(frames: Vec<ArtFrame>) -> Self {
+    ArtVariant {
+      size: get_art_size(&frames),
+      frames,
+    }
+  }
+}
+
+struct AnimatedArt {
+  // the override or the default background the art was picked from
+  scheduled_path: Option<PathBuf>,
+  art: Option<ArtVariant>,
+  // drawn instead when the art doesn't fit the screen
+  small_art: Option<ArtVariant>,
+  start: Instant,
+  last_schedule_check: Instant,
+}
+
+impl AnimatedArt {
+  fn get_variant(&self, screen_size: Vec2) -> Option<&ArtVariant> {
+    match (&self.art, &self.small_art) {
+      (Some(art), Some(small_art)) if !art.size.fits_in(screen_size) => {
+        Some(small_art)
       }
-      // TODO: Give better error message
-      Err(_) => return Err(TUILogError::BackgroundArtFailed),
-    },
-    None => {}
+      (art, _) => art.as_ref(),
+    }
+  }
+
+  // swaps the art once an override starts or ends
+  fn update_schedule(&mut self) {
+    if self.last_schedule_check.elapsed() < SCHEDULE_CHECK_INTERVAL {
+      return;
+    }
+    self.last_schedule_check = Instant::now();
+
+    let scheduled_path = get_scheduled_background_path();
+    if scheduled_path == self.scheduled_path {
+      return;
+    }
+
+    self.art = load_art(scheduled_path.as_deref()).unwrap_or(None);
+    self.scheduled_path = scheduled_path;
+    self.start = Instant::now();
+  }
+
+  // the frame is picked from the time so it doesn't depend on the refresh rate
+  fn current_frame<'a>(&self, art: &'a ArtVariant) -> &'a ArtFrame {
+    let frame_count = art.frames.len();
+    let elapsed_frames = (self.start.elapsed().as_millis()
+      * *background_ascii_art_fps as u128
+      / 1000) as usize;
+
+    let frame = if *background_ascii_art_loop {
+      elapsed_frames % frame_count
+    } else {
+      elapsed_frames.min(frame_count - 1)
+    };
+
+    &art.frames[frame]
+  }
+}
+
+// blank frames, like after a trailing separator, are dropped so they don't
+// blink the art away
+fn is_blank_frame(frame: &str) -> bool {
+  frame.trim().is_empty()
+}
+
+fn split_frames(ascii_art: &str, separator: &str) -> Vec<String> {
+  let mut frames = vec![String::new()];
+
+  for line in ascii_art.lines() {
+    if line.trim_end() == separator {
+      frames.push(String::new());
+    } else if let Some(frame) = frames.last_mut() {
+      frame.push_str(line);
+      frame.push('\n');
+    }
+  }
+
+  frames.retain(|frame| !is_blank_frame(frame));
+  frames
+}
+
+// a directory holds one frame per file, in the order of the file names
+fn read_text_frames(path: &Path) -> io::Result<Vec<String>> {
+  if path.is_dir() {
+    let mut frame_paths: Vec<PathBuf> = fs::read_dir(path)?
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| path.is_file())
+      .collect();
+    frame_paths.sort();
+
+    let frames: io::Result<Vec<String>> =
+      frame_paths.iter().map(fs::read_to_string).collect();
+    return frames.map(|mut frames| {
+      frames.retain(|frame| !is_blank_frame(frame));
+      frames
+    });
+  }
+
+  let ascii_art = fs::read_to_string(path)?;
+  Ok(match *background_ascii_art_frame_separator {
+    Some(ref separator) => split_frames(&ascii_art, separator),
+    None => vec![ascii_art],
+  })
+}
+
+fn read_frames(path: &Path) -> io::Result<Vec<ArtFrame>> {
+  Ok(
+    read_text_frames(path)?
+      .iter()
+      .map(|frame| parse_ansi_text(frame))
+      .collect(),
+  )
+}
+
+fn line_width(line: &[AnsiSpan]) -> usize {
+  line.iter().map(|span| span.text.width()).sum()
+}
+
+// the width and height taken by the largest frame
+fn get_art_size(frames: &[ArtFrame]) -> Vec2 {
+  let width = frames
+    .iter()
+    .flatten()
+    .map(|line| line_width(line))
+    .max()
+    .unwrap_or(0);
+  let height = frames.iter().map(Vec::len).max().unwrap_or(0);
+
+  Vec2::new(width, height)
+}
+
+// times are in minutes since midnight, the range wraps around midnight when
+// it ends before it starts
+fn is_time_between(time: u32, from: u32, to: u32) -> bool {
+  if from <= to {
+    from <= time && time < to
+  } else {
+    time >= from || time < to
+  }
+}
+
+// the time and date are None when the local time can't be read, which only
+// leaves the overrides without them active
+fn is_override_active(
+  background_override: &BackgroundOverride,
+  tty: Option<&str>,
+  time: Option<u32>,
+  date: Option<(u32, u32)>,
+) -> bool {
+  let is_time_active = match (background_override.from, background_override.to)
+  {
+    (None, None) => true,
+    (from, to) => time.is_some_and(|time| {
+      is_time_between(time, from.unwrap_or(0), to.unwrap_or(MINUTES_PER_DAY))
+    }),
   };
 
-  Ok(())
+  background_override
+    .tty
+    .as_deref()
+    .is_none_or(|override_tty| Some(override_tty) == tty)
+    && (background_override.dates.is_empty()
+      || date.is_some_and(|date| background_override.dates.contains(&date)))
+    && is_time_active
+}
+
+// whether the active override may change as time goes on
+fn is_scheduled(background_override: &BackgroundOverride) -> bool {
+  background_override.from.is_some()
+    || background_override.to.is_some()
+    || !background_override.dates.is_empty()
+}
+
+fn fits_screen(path: &Path, screen_size: (usize, usize)) -> bool {
+  read_frames(path)
+    .is_ok_and(|frames| get_art_size(&frames).fits_in(screen_size))
+}
+
+fn pick_random_art(directory: &Path) -> Option<PathBuf> {
+  let screen_size = if *background_ascii_art_fit_to_screen {
+    get_terminal_size()
+  } else {
+    None
+  };
+
+  let art_paths: Vec<PathBuf> = fs::read_dir(directory)
+    .ok()?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| path.is_file())
+    .filter(|path| screen_size.is_none_or(|size| fits_screen(path, size)))
+    .collect();
+
+  let mut random = Random::from_time();
+  art_paths.get(random.below(art_paths.len())).cloned()
+}
+
+// the first active override wins over the default background
+fn get_scheduled_background_path() -> Option<PathBuf> {
+  let tty = get_current_tty();
+  let time = parse_time_of_day(&format_local_time("%H:%M"));
+  let date = parse_date(&format_local_time("%m-%d"));
+
+  background_ascii_art_overrides
+    .iter()
+    .find(|background_override| {
+      is_override_active(background_override, tty.as_deref(), time, date)
+    })
+    .map(BackgroundOverride::background_path)
+    .or_else(|| background_ascii_art_path.clone())
+}
+
+fn pick_background_path(path: &Path) -> Option<PathBuf> {
+  if *background_ascii_art_random && path.is_dir() {
+    pick_random_art(path)
+  } else {
+    Some(path.to_path_buf())
+  }
+}
+
+// picks the art of the scheduled background, None when there is none
+fn load_art(scheduled_path: Option<&Path>) -> TUILogResult<Option<ArtVariant>> {
+  let path = scheduled_path.and_then(pick_background_path);
+  if let Ok(mut current_path) = background_path.lock() {
+    current_path.clone_from(&path);
+  }
+
+  let Some(path) = path else {
+    return Ok(None);
+  };
+  match read_frames(&path) {
+    Ok(frames) if !frames.is_empty() => Ok(Some(ArtVariant::new(frames))),
+    // TODO: Give better error message
+    _ => Err(TUILogError::BackgroundArtFailed),
+  }
+}
+
+// the first frame of the background without its colors
+pub fn get_background_text() -> Option<String> {
+  let path = background_path.lock().ok()?.clone()?;
+  let frames = read_frames(&path).ok()?;
+  let text = frames
+    .first()?
+    .iter()
+    .map(|line| line.iter().map(|span| span.text.as_str()).collect())
+    .collect::<Vec<String>>()
+    .join("\n");
+
+  Some(text)
+}
+
+// the offset of the art on one axis, negative when it is cropped
+fn get_align_offset(
+  screen_length: usize,
+  art_length: usize,
+  align: usize,
+) -> isize {
+  // the align is 0 for the start, 1 for the center and 2 for the end
+  (screen_length as isize - art_length as isize) * align as isize / 2
+}
+
+// draws the frame with its top left corner at the given position, the parts
+// off the screen are left out
+fn draw_frame(printer: &Printer, frame: &ArtFrame, position: (isize, isize)) {
+  let background_color = get_base_theme_ref().palette[PaletteColor::Background];
+
+  for (i, line) in frame.iter().enumerate() {
+    let y = position.1 + i as isize;
+    if y < 0 {
+      continue;
+    }
+
+    let mut x = position.0;
+    for span in line {
+      // wide characters only partly on the screen are left out
+      let mut text = String::new();
+      let mut text_x = None;
+      for c in span.text.chars() {
+        if x >= 0 {
+          text_x.get_or_insert(x);
+          text.push(c);
+        }
+        x += c.width().unwrap_or(0) as isize;
+      }
+      let Some(text_x) = text_x else {
+        continue;
+      };
+
+      // colors missing in the art fall back to the configured ones
+      let foreground = span
+        .style
+        .foreground
+        .unwrap_or(Color::Dark(background_ascii_art_color.clone()));
+      let background = span.style.background.unwrap_or(background_color);
+
+      printer.with_color(
+        ColorStyle::new(
+          ColorType::Color(foreground),
+          ColorType::Color(background),
+        ),
+        |printer| {
+          let position = (text_x as usize, y as usize);
+          if span.style.bold {
+            printer.with_effect(Effect::Bold, |printer| {
+              printer.print(position, &text);
+            });
+          } else {
+            printer.print(position, &text);
+          }
+        },
+      );
+    }
+  }
+}
+
+fn draw_art(animated_art: &AnimatedArt, printer: &Printer) {
+  let Some(art) = animated_art.get_variant(printer.size) else {
+    return;
+  };
+  let frame = animated_art.current_frame(art);
+
+  if *background_ascii_art_align == BackgroundAlign::Tiled {
+    for y in (0..printer.size.y).step_by(art.size.y.max(1)) {
+      for x in (0..printer.size.x).step_by(art.size.x.max(1)) {
+        draw_frame(printer, frame, (x as isize, y as isize));
+      }
+    }
+    return;
+  }
+
+  if *background_ascii_art_crop == BackgroundCrop::Hide
+    && !art.size.fits_in(printer.size)
+  {
+    return;
+  }
+
+  let (x_align, y_align) = match *background_ascii_art_align {
+    BackgroundAlign::TopLeft => (0, 0),
+    BackgroundAlign::TopRight => (2, 0),
+    BackgroundAlign::BottomLeft => (0, 2),
+    BackgroundAlign::BottomRight => (2, 2),
+    _ => (1, 1),
+  };
+
+  draw_frame(
+    printer,
+    frame,
+    (
+      get_align_offset(printer.size.x, art.size.x, x_align),
+      get_align_offset(printer.size.y, art.size.y, y_align),
+    ),
+  );
+}
+
+pub fn draw_background_ascii_art(stack: &mut StackView) -> TUILogResult<()> {
+  let scheduled_path = get_scheduled_background_path();
+  if scheduled_path.is_none() && background_ascii_art_overrides.is_empty() {
+    return Ok(());
+  }
+
+  let (art, result) = match load_art(scheduled_path.as_deref()) {
+    Ok(art) => (art, Ok(())),
+    Err(error) => (None, Err(error)),
+  };
+  let small_art = background_ascii_art_small_path
+    .as_ref()
+    .and_then(|path| read_frames(path).ok())
+    .filter(|frames| !frames.is_empty())
+    .map(ArtVariant::new);
+
+  // Create a Canvas to render ASCII art
+  let ascii_view = Canvas::new(AnimatedArt {
+    scheduled_path,
+    art,
+    small_art,
+    start: Instant::now(),
+    last_schedule_check: Instant::now(),
+  })
+  .with_draw(draw_art)
+  // the views are laid out on every refresh
+  .with_layout(|animated_art, _| animated_art.update_schedule());
+
+  // Add the ASCII art as the background
+  stack.add_fullscreen_layer(Layer::new(ascii_view.full_screen()));
+
+  result
+}
+
+// the screen is only redrawn as often as the animation needs, or as the
+// overrides need to be checked
+pub fn animate_background_ascii_art(siv: &mut Cursive) {
+  // every background the overrides may switch to
+  let is_animated = background_ascii_art_path
+    .iter()
+    .cloned()
+    .chain(
+      background_ascii_art_overrides
+        .iter()
+        .map(BackgroundOverride::background_path),
+    )
+    .any(|path| {
+      (path.is_dir() && !*background_ascii_art_random)
+        || background_ascii_art_frame_separator.is_some()
+    });
+
+  if is_animated {
+    set_min_fps(siv, *background_ascii_art_fps);
+  } else if background_ascii_art_overrides.iter().any(is_scheduled) {
+    set_min_fps(siv, 1);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn splits_frames_on_separator_lines() {
+    assert_eq!(split_frames("a\nb\n---\nc\n", "---"), vec!["a\nb\n", "c\n"]);
+    // trailing spaces after the separator are allowed
+    assert_eq!(split_frames("a\n---  \nc", "---"), vec!["a\n", "c\n"]);
+  }
+
+  #[test]
+  fn drops_empty_frames() {
+    assert_eq!(split_frames("a\n---\n", "---"), vec!["a\n"]);
+    assert_eq!(
+      split_frames("---\na\n---\n---\nb", "---"),
+      vec!["a\n", "b\n"]
+    );
+    assert!(split_frames("---\n", "---").is_empty());
+    assert_eq!(
+      split_frames("a\n---\n  \n\n---\nb", "---"),
+      vec!["a\n", "b\n"]
+    );
+  }
+
+  #[test]
+  fn drops_blank_frame_files() {
+    let directory = std::env::temp_dir()
+      .join(format!("tuilog-frames-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("1.txt"), "a\n").unwrap();
+    fs::write(directory.join("2.txt"), " \n\n").unwrap();
+    fs::write(directory.join("3.txt"), "b\n").unwrap();
+
+    let frames = read_text_frames(&directory);
+    fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(frames.unwrap(), vec!["a\n", "b\n"]);
+  }
+
+  fn parse_override(background_override: &str) -> BackgroundOverride {
+    toml::from_str(background_override).unwrap()
+  }
+
+  #[test]
+  fn checks_time_ranges() {
+    assert!(is_time_between(600, 540, 1020));
+    assert!(!is_time_between(1020, 540, 1020));
+    assert!(!is_time_between(500, 540, 1020));
+  }
+
+  #[test]
+  fn checks_time_ranges_wrapping_around_midnight() {
+    assert!(is_time_between(1380, 1200, 360));
+    assert!(is_time_between(0, 1200, 360));
+    assert!(is_time_between(300, 1200, 360));
+    assert!(!is_time_between(360, 1200, 360));
+    assert!(!is_time_between(720, 1200, 360));
+  }
+
+  #[test]
+  fn matches_overrides_by_time() {
+    // "6:00" and "06:00" are the same time
+    let night = parse_override(
+      r#"
+      background = "night"
+      from = "20:00"
+      to = "6:00"
+      "#,
+    );
+    assert!(is_override_active(&night, None, Some(330), Some((1, 1))));
+    assert!(!is_override_active(&night, None, Some(360), Some((1, 1))));
+    assert!(!is_override_active(&night, None, None, Some((1, 1))));
+
+    let evening = parse_override(
+      r#"
+      background = "evening"
+      from = "18:00"
+      "#,
+    );
+    assert!(is_override_active(&evening, None, Some(1439), None));
+    assert!(!is_override_active(&evening, None, Some(1079), None));
+  }
+
+  #[test]
+  fn matches_overrides_by_tty_and_date() {
+    let holiday = parse_override(
+      r#"
+      background = "holiday"
+      tty = "tty2"
+      dates = ["12-24", "12-25"]
+      "#,
+    );
+    assert!(is_override_active(
+      &holiday,
+      Some("tty2"),
+      None,
+      Some((12, 25))
+    ));
+    assert!(!is_override_active(
+      &holiday,
+      Some("tty1"),
+      None,
+      Some((12, 25))
+    ));
+    assert!(!is_override_active(
+      &holiday,
+      Some("tty2"),
+      None,
+      Some((12, 26))
+    ));
+    assert!(is_scheduled(&holiday));
+
+    let tty_only = parse_override(
+      r#"
+      background = "tty"
+      tty = "tty2"
+      "#,
+    );
+    assert!(is_override_active(&tty_only, Some("tty2"), None, None));
+    assert!(!is_scheduled(&tty_only));
+  }
 }
@@ -1,25 +1,38 @@
 use cursive::align::HAlign;
+use cursive::event::EventResult;
 use cursive::view::{Nameable, Offset, Resizable, View};
 use cursive::views::{
-  stack_view::LayerAt, Button, Dialog, EditView, LinearLayout, PaddedView,
-  SelectView, StackView, TextView, ThemedView,
+  stack_view::LayerAt, Button, Dialog, EditView, FocusTracker, LinearLayout,
+  NamedView, PaddedView, Panel, ScrollView, SelectView, StackView, TextView,
+  ThemedView,
 };
-use cursive::Cursive;
 use cursive::XY;
+use cursive::{Cursive, With};
 use std::cmp::max;
 use text_to_ascii_art::to_art;
+use unicode_width::UnicodeWidthStr;
+use users::get_user_by_name;
 
 use crate::error::DrawTUILogResult;
-use crate::session::start_session;
-use crate::state::{get_default_options, sessions, title};
+use crate::session::{
+  authorize_power_action, is_session_allowed, start_session, unlock_session,
+};
+use crate::state::{
+  get_accounts, get_default_options, get_issue, get_user_session, issue_config,
+  sessions, title, title_art, user_picker, Session,
+};
 use crate::sys_ctrl::{reboot, shutdown};
 use crate::tui::{
   get_accent_message_theme, get_edit_view_theme, get_error_message_theme,
-  get_hostname_art_theme,
+  get_hostname_art_theme, hide_password, PasswordView,
+};
+use crate::utils::{
+  count_wrapped_lines, get_current_tty, get_terminal_size, longest_line_length,
 };
-use crate::utils::{get_current_tty, longest_line_length};
 
 const INPUT_LENGTH: usize = 24;
+// the border and padding of the dialog around the title
+const TITLE_MARGIN: usize = 6;
 
 fn draw_input_field<T: View>(
   label: &str,
@@ -60,39 +73,217 @@ fn draw_button<T: 'static + Fn(&mut Cursive) + Send + Sync>(
   )
 }
 
-pub fn draw_content_box(stack: &mut StackView) {
-  let hostname_art = match to_art(title.to_string(), "standard", 0, 1, 0) {
-    Ok(art) => art,
-    Err(_) => title.to_string(),
+// the spacing to the left and right of a select field to center it, a field
+// wider than the box is left unpadded
+fn get_select_spacing(select_width: usize, max_width: usize) -> (usize, usize) {
+  // NOTE: the end decorators update this on changing decorators
+  let select_width = select_width + 4;
+  let left_spacing = max_width.saturating_sub(select_width) / 2;
+  let right_spacing = max_width
+    .saturating_sub(left_spacing)
+    .saturating_sub(select_width);
+
+  (left_spacing, right_spacing)
+}
+
+fn draw_select_field<T: View>(
+  select_width: usize,
+  max_width: usize,
+  top_spacing: usize,
+  select_view: T,
+) -> impl View {
+  let (left_spacing, right_spacing) =
+    get_select_spacing(select_width, max_width);
+
+  PaddedView::lrtb(
+    left_spacing,
+    right_spacing,
+    top_spacing,
+    1,
+    LinearLayout::horizontal()
+      .child(ThemedView::new(
+        get_accent_message_theme(),
+        TextView::new("< "),
+      ))
+      .child(select_view)
+      .child(ThemedView::new(
+        get_accent_message_theme(),
+        TextView::new(" >"),
+      )),
+  )
+}
+
+fn get_session_details(session: &Session) -> String {
+  [&session.description, &session.comment]
+    .into_iter()
+    .flatten()
+    .map(|detail| detail.trim())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+// the icon and details of the selected session, which is sized to fit the
+// session with the most details so the box doesn't change size
+fn draw_session_info(max_width: usize) -> Option<impl View> {
+  let icon_width = sessions
+    .iter()
+    .filter_map(|session| session.icon.as_deref())
+    .map(longest_line_length)
+    .max()
+    .unwrap_or(0);
+  let icon_spacing = if icon_width > 0 { 2 } else { 0 };
+  let details_width = max_width.saturating_sub(icon_width + icon_spacing);
+
+  let height = sessions
+    .iter()
+    .map(|session| {
+      max(
+        session
+          .icon
+          .as_deref()
+          .map_or(0, |icon| icon.lines().count()),
+        count_wrapped_lines(&get_session_details(session), details_width),
+      )
+    })
+    .max()
+    .unwrap_or(0);
+
+  if height == 0 {
+    return None;
+  }
+
+  let session = sessions.first();
+  Some(PaddedView::lrtb(
+    0,
+    0,
+    0,
+    1,
+    LinearLayout::horizontal()
+      .child(ThemedView::new(
+        get_hostname_art_theme(),
+        TextView::new(
+          session
+            .and_then(|session| session.icon.clone())
+            .unwrap_or_default(),
+        )
+        .with_name("session_icon")
+        .fixed_width(icon_width),
+      ))
+      .child(PaddedView::lrtb(
+        icon_spacing,
+        0,
+        0,
+        0,
+        TextView::new(session.map(get_session_details).unwrap_or_default())
+          .with_name("session_details")
+          .fixed_width(details_width),
+      ))
+      .fixed_height(height),
+  ))
+}
+
+fn update_session_info(siv: &mut Cursive, session_index: usize) {
+  let Some(session) = sessions.get(session_index) else {
+    return;
   };
 
-  let hostname_art_width = longest_line_length(&hostname_art);
-  // NOTE: the number 12 is very sensitive, any change in length of label or
-  // text field length must be noted
-  let input_combined_length = INPUT_LENGTH + 12;
+  siv.call_on_name("session_icon", |view: &mut TextView| {
+    view.set_content(session.icon.clone().unwrap_or_default());
+  });
+  siv.call_on_name("session_details", |view: &mut TextView| {
+    view.set_content(get_session_details(session));
+  });
+}
 
-  let max_width = max(input_combined_length, hostname_art_width);
-  // The padded space to the left of each input fields to center it
-  let input_left_padding = (max_width - input_combined_length) / 2;
+// puts the user picked from the user picker in the username field
+fn fill_username(siv: &mut Cursive, username: &str) {
+  siv.call_on_name("username", |view: &mut EditView| {
+    view.set_content(username);
+  });
+  on_username_change(siv, username);
+}
 
-  let mut session_select = SelectView::new().h_align(HAlign::Left);
+fn draw_user_select(max_width: usize) -> Option<impl View> {
+  if !user_picker.enable {
+    return None;
+  }
 
-  let mut session_select_width = 0;
+  let accounts = get_accounts();
+  if accounts.is_empty() {
+    return None;
+  }
 
-  for (i, session) in sessions.iter().enumerate() {
-    session_select_width = max(session.name.len(), session_select_width);
-    session_select.add_item(session.name.clone(), i);
+  let mut user_select = SelectView::new().h_align(HAlign::Left);
+  let mut user_select_width = 0;
+
+  for account in accounts {
+    let display_name = account.display_name().to_string();
+    user_select_width = max(display_name.width(), user_select_width);
+    user_select.add_item(display_name, account.username);
   }
 
-  session_select.set_selection(0);
+  Some(draw_select_field(
+    user_select_width,
+    max_width,
+    0,
+    user_select
+      .decorators("", "")
+      .autojump()
+      .on_select(|siv, username: &String| {
+        fill_username(siv, username);
+      })
+      // picking from the popup menu only submits the entry
+      .on_submit(|siv, username: &String| {
+        fill_username(siv, username);
+        siv.focus_name("password").ok();
+      })
+      .popup()
+      .with_name("user"),
+  ))
+}
 
-  // NOTE: the end decorators update this on changing decorators
-  session_select_width += 4;
+fn draw_issue_panel(issue: String, max_width: usize) -> impl View {
+  PaddedView::lrtb(
+    0,
+    0,
+    1,
+    0,
+    Panel::new(
+      ScrollView::new(TextView::new(issue)).max_height(issue_config.height),
+    )
+    .fixed_width(max_width),
+  )
+}
+
+// each line of the title is drawn on its own
+fn render_title(font: &str) -> Option<String> {
+  title
+    .lines()
+    .map(|line| to_art(line.to_string(), font, 0, title_art.spacing, 0).ok())
+    .collect::<Option<Vec<String>>>()
+    .map(|lines| lines.join("\n"))
+}
+
+pub fn get_hostname_art() -> String {
+  if title_art.plain {
+    return title.to_string();
+  }
+
+  let max_width =
+    get_terminal_size().map(|(width, _)| width.saturating_sub(TITLE_MARGIN));
+  let fits_screen = |art: &String| {
+    max_width.is_none_or(|max_width| longest_line_length(art) <= max_width)
+  };
 
-  let session_select_left_padding = (max_width - session_select_width) / 2;
-  let session_select_right_padding =
-    max_width - session_select_left_padding - session_select_width;
+  // the plain title is used when it can't be drawn in any font
+  std::iter::once(&title_art.font)
+    .chain(title_art.fallback_font.as_ref())
+    .filter_map(|font| render_title(font))
+    .find(fits_screen)
+    .unwrap_or_else(|| title.to_string())
+}
 
+fn draw_tty_label(stack: &mut StackView) {
   stack.add_layer(LayerAt(
     XY::new(Offset::Absolute(2), Offset::Absolute(1)),
     match get_current_tty() {
@@ -104,6 +295,42 @@ pub fn draw_content_box(stack: &mut StackView) {
       }
     },
   ));
+}
+
+fn draw_caps_lock_warning(max_width: usize) -> impl View {
+  ThemedView::new(
+    get_error_message_theme(),
+    TextView::new("")
+      .h_align(HAlign::Center)
+      .with_name("caps_lock_warning")
+      .fixed_size((max_width, 1)),
+  )
+}
+
+pub fn draw_content_box(stack: &mut StackView) {
+  let hostname_art = get_hostname_art();
+
+  let hostname_art_width = longest_line_length(&hostname_art);
+  // NOTE: the number 12 is very sensitive, any change in length of label or
+  // text field length must be noted
+  let input_combined_length = INPUT_LENGTH + 12;
+
+  let max_width = max(input_combined_length, hostname_art_width);
+  // The padded space to the left of each input fields to center it
+  let input_left_padding = (max_width - input_combined_length) / 2;
+
+  let mut session_select = SelectView::new().h_align(HAlign::Left);
+
+  let mut session_select_width = 0;
+
+  for (i, session) in sessions.iter().enumerate() {
+    session_select_width = max(session.name.len(), session_select_width);
+    session_select.add_item(session.name.clone(), i);
+  }
+
+  session_select.set_selection(0);
+
+  draw_tty_label(stack);
 
   stack.add_layer(LayerAt(
     XY::center(),
@@ -117,74 +344,155 @@ pub fn draw_content_box(stack: &mut StackView) {
           get_hostname_art_theme(),
           TextView::new(hostname_art).h_align(HAlign::Center),
         ))
-        .child(PaddedView::lrtb(
-          session_select_left_padding,
-          session_select_right_padding,
-          1,
+        .child(draw_select_field(
+          session_select_width,
+          max_width,
           1,
-          LinearLayout::horizontal()
-            .child(ThemedView::new(
-              get_accent_message_theme(),
-              TextView::new("< "),
-            ))
-            .child(
-              session_select
-                .decorators("", "")
-                .autojump()
-                .on_submit(|siv, _| {
-                  siv.focus_name("username").ok();
-                })
-                .popup()
-                .with_name("session"),
-            )
-            .child(ThemedView::new(
-              get_accent_message_theme(),
-              TextView::new(" >"),
-            )),
+          session_select
+            .decorators("", "")
+            .autojump()
+            .on_select(|siv, session_index| {
+              update_session_info(siv, *session_index);
+            })
+            .on_submit(|siv, session_index| {
+              update_session_info(siv, *session_index);
+              siv.focus_name("username").ok();
+            })
+            .popup()
+            .with_name("session"),
         ))
+        .with(|layout| {
+          if let Some(session_info) = draw_session_info(max_width) {
+            layout.add_child(session_info);
+          }
+        })
+        .with(|layout| {
+          if let Some(user_select) = draw_user_select(max_width) {
+            layout.add_child(user_select);
+          }
+        })
         .child(draw_input_field(
           "USERNAME",
           input_left_padding,
-          EditView::new()
-            .filler(" ")
-            .on_submit(|siv, _| {
-              siv.focus_name("password").ok();
+          // the sessions are only looked up once the username is entered,
+          // not on every keystroke
+          FocusTracker::new(
+            EditView::new()
+              .filler(" ")
+              .on_submit(|siv, username| {
+                on_username_change(siv, username);
+                siv.focus_name("password").ok();
+              })
+              .with_name("username"),
+          )
+          .on_focus_lost(|view: &mut NamedView<EditView>| {
+            let username = view.get_mut().get_content();
+            EventResult::with_cb(move |siv| {
+              on_username_change(siv, &username);
             })
-            .with_name("username")
-            .fixed_width(INPUT_LENGTH),
+          })
+          .fixed_width(INPUT_LENGTH),
         ))
         .child(draw_input_field(
           "PASSWORD",
           input_left_padding,
-          EditView::new()
-            .secret()
-            .filler(" ")
-            .on_submit(|siv, _| {
-              start_session(siv).draw_on_err(siv);
-            })
-            .with_name("password")
-            .fixed_width(INPUT_LENGTH),
+          PasswordView::new(EditView::new().filler(" ").on_submit(|siv, _| {
+            start_session(siv).draw_on_err(siv);
+          }))
+          .with_name("password")
+          .fixed_width(INPUT_LENGTH),
         ))
+        // takes the place of the padding above the buttons
+        .child(draw_caps_lock_warning(max_width))
         .child(
           // NOTE: The number 36 has been precisely caliberated,
           // anychanges should be noted
           PaddedView::lrtb(
             (max_width - 36) / 2 + 1,
             0,
-            1,
+            0,
             0,
             LinearLayout::horizontal()
               .child(draw_button("LOGIN", |siv: &mut Cursive| {
                 start_session(siv).draw_on_err(siv);
               }))
               .child(draw_button("SHUTDOWN", |siv: &mut Cursive| {
-                shutdown().draw_on_err(siv);
+                authorize_power_action(siv)
+                  .and_then(|_| shutdown())
+                  .draw_on_err(siv);
               }))
               .child(draw_button("REBOOT", |siv: &mut Cursive| {
-                reboot().draw_on_err(siv);
+                authorize_power_action(siv)
+                  .and_then(|_| reboot())
+                  .draw_on_err(siv);
               })),
           ),
-        ),
+        )
+        .with(|layout| {
+          if let Some(issue) = get_issue() {
+            layout.add_child(draw_issue_panel(issue, max_width));
+          }
+        }),
+    )),
+  ));
+}
+
+// the greeter of the lock screen, for the given user only
+pub fn draw_lock_box(stack: &mut StackView, username: String) {
+  let hostname_art = get_hostname_art();
+  let max_width = max(INPUT_LENGTH + 12, longest_line_length(&hostname_art));
+  let input_left_padding = (max_width - INPUT_LENGTH - 12) / 2;
+  // NOTE: the unlock button with its left padding is 10 characters long
+  let button_left_padding = (max_width - 10) / 2;
+
+  let submit_username = username.clone();
+
+  draw_tty_label(stack);
+
+  stack.add_layer(LayerAt(
+    XY::center(),
+    Dialog::around(PaddedView::lrtb(
+      2,
+      2,
+      1,
+      1,
+      LinearLayout::vertical()
+        .child(ThemedView::new(
+          get_hostname_art_theme(),
+          TextView::new(hostname_art).h_align(HAlign::Center),
+        ))
+        .child(PaddedView::lrtb(
+          0,
+          0,
+          1,
+          0,
+          draw_input_field(
+            "USERNAME",
+            input_left_padding,
+            TextView::new(username.clone()).fixed_width(INPUT_LENGTH),
+          ),
+        ))
+        .child(draw_input_field(
+          "PASSWORD",
+          input_left_padding,
+          PasswordView::new(EditView::new().filler(" ").on_submit(
+            move |siv, _| {
+              unlock_session(siv, &submit_username).draw_on_err(siv);
+            },
+          ))
+          .with_name("password")
+          .fixed_width(INPUT_LENGTH),
+        ))
+        .child(draw_caps_lock_warning(max_width))
+        .child(PaddedView::lrtb(
+          button_left_padding,
+          0,
+          0,
+          0,
+          draw_button("UNLOCK", move |siv: &mut Cursive| {
+            unlock_session(siv, &username).draw_on_err(siv);
+          }),
+        )),
     )),
   ));
 }
@@ -196,22 +504,123 @@ pub fn set_default_values(siv: &mut Cursive) {
       siv.call_on_name("username", |view: &mut EditView| {
         view.set_content(username);
       });
+      filter_sessions(siv, username);
+      siv.call_on_name("user", |view: &mut SelectView<String>| {
+        let user_index = view.iter().position(|(_, value)| value == username);
+        if let Some(user_index) = user_index {
+          view.set_selection(user_index);
+        }
+      });
 
       siv.focus_name("password").ok();
     }
     None => {
+      // the user picker shows its first account without selecting it
+      let username = siv
+        .call_on_name("user", |view: &mut SelectView<String>| {
+          view.selection().map(|username| username.to_string())
+        })
+        .flatten();
+      if let Some(ref username) = username {
+        fill_username(siv, username);
+      }
+
       siv.focus_name("username").ok();
     }
   };
 
   if let Some(ref session_name) = default_options.session_name {
-    siv.call_on_name("session", |view: &mut SelectView<usize>| {
-      if let Some(session_index) = sessions
+    select_session(siv, session_name);
+  }
+}
+
+// clears whatever was typed and goes back to the remembered options
+pub fn reset_form(siv: &mut Cursive) {
+  siv.call_on_name("username", |view: &mut EditView| {
+    view.set_content("");
+  });
+  siv.call_on_name("password", |view: &mut PasswordView| {
+    view.clear();
+  });
+  hide_password(siv);
+  filter_sessions(siv, "");
+  set_default_values(siv);
+}
+
+fn select_session(siv: &mut Cursive, session_name: &str) {
+  // the selector may only hold some of the sessions, so look it up by value
+  let session_index = siv
+    .call_on_name("session", |view: &mut SelectView<usize>| {
+      let position = view
         .iter()
-        .position(|session| &session.name == session_name)
-      {
-        view.set_selection(session_index);
+        .position(|(_, i)| sessions[*i].name == session_name)?;
+      view.set_selection(position);
+      view.selection().map(|i| *i)
+    })
+    .flatten();
+
+  if let Some(session_index) = session_index {
+    update_session_info(siv, session_index);
+  }
+}
+
+// only lists the sessions the user may start, or all of them while the user
+// is unknown
+fn filter_sessions(siv: &mut Cursive, username: &str) {
+  let user = get_user_by_name(username);
+  let session_index = siv
+    .call_on_name("session", |view: &mut SelectView<usize>| {
+      let selected = view.selection().map(|i| *i);
+      view.clear();
+
+      for (i, session) in sessions.iter().enumerate() {
+        if user
+          .as_ref()
+          .is_none_or(|user| is_session_allowed(session, user))
+        {
+          view.add_item(session.name.clone(), i);
+        }
       }
-    });
+
+      let position = view
+        .iter()
+        .position(|(_, i)| Some(*i) == selected)
+        .unwrap_or(0);
+      view.set_selection(position);
+      view.selection().map(|i| *i)
+    })
+    .flatten();
+
+  if let Some(session_index) = session_index {
+    update_session_info(siv, session_index);
+  }
+}
+
+// switches the session selector to the last session used by the user
+fn select_user_session(siv: &mut Cursive, username: &str) {
+  if let Some(session_name) = get_user_session(username) {
+    select_session(siv, &session_name);
+  }
+}
+
+fn on_username_change(siv: &mut Cursive, username: &str) {
+  filter_sessions(siv, username);
+  select_user_session(siv, username);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn select_spacing_centers_the_field() {
+    assert_eq!(get_select_spacing(10, 36), (11, 11));
+    assert_eq!(get_select_spacing(11, 36), (10, 11));
+  }
+
+  #[test]
+  fn select_spacing_does_not_underflow_for_wide_fields() {
+    assert_eq!(get_select_spacing(33, 36), (0, 0));
+    assert_eq!(get_select_spacing(100, 36), (0, 0));
   }
 }
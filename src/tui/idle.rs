@@ -0,0 +1,80 @@
+use cursive::event::{Event, EventTrigger};
+use cursive::Cursive;
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::state::idle_config;
+use crate::tui::{
+  draw_blank_screen, draw_screensaver, reset_form, show_notice,
+};
+
+lazy_static! {
+  static ref last_activity: Mutex<Instant> = Mutex::new(Instant::now());
+}
+
+fn get_idle_time() -> Duration {
+  last_activity.lock().unwrap().elapsed()
+}
+
+// resets the idle time on every input from the user
+pub fn track_idle_time(siv: &mut Cursive) {
+  siv.set_on_pre_event_inner(
+    EventTrigger::from_fn(|event| *event != Event::Refresh),
+    |_| {
+      *last_activity.lock().unwrap() = Instant::now();
+      None
+    },
+  );
+}
+
+// raises the refresh rate to at least the given frames per second
+pub fn set_min_fps(siv: &mut Cursive, fps: u32) {
+  if siv.fps().is_none_or(|current_fps| current_fps.get() < fps) {
+    siv.set_fps(fps);
+  }
+}
+
+// calls the callback once each time the user stays idle for the timeout
+pub fn on_idle<F>(siv: &mut Cursive, timeout: Duration, mut callback: F)
+where
+  F: FnMut(&mut Cursive) + Send + Sync + 'static,
+{
+  // the idle time is checked on every refresh
+  set_min_fps(siv, 1);
+
+  let mut has_fired = false;
+  siv.set_on_pre_event(Event::Refresh, move |siv| {
+    if get_idle_time() < timeout {
+      has_fired = false;
+    } else if !has_fired {
+      has_fired = true;
+      callback(siv);
+    }
+  });
+}
+
+fn reset_greeter(siv: &mut Cursive) {
+  // dialogs and popups are layers above the greeter
+  let has_notice = siv.debug_name("notice").is_some();
+  while siv.screen().len() > 1 {
+    siv.pop_layer();
+  }
+  reset_form(siv);
+  // the notice has to be accepted again
+  if has_notice {
+    show_notice(siv);
+  }
+
+  match idle_config.screensaver {
+    Some(kind) => draw_screensaver(siv, kind),
+    None if idle_config.blank => draw_blank_screen(siv),
+    None => {}
+  }
+}
+
+pub fn reset_on_idle(siv: &mut Cursive) {
+  if let Some(timeout) = idle_config.timeout {
+    on_idle(siv, Duration::from_secs(timeout), reset_greeter);
+  }
+}
@@ -0,0 +1,99 @@
+use cursive::event::{Event, Key};
+use cursive::view::Resizable;
+use cursive::views::{
+  DummyView, LinearLayout, PaddedView, StackView, TextView, ThemedView,
+};
+use cursive::Cursive;
+
+use crate::error::DrawTUILogResult;
+use crate::session::authorize_power_action;
+use crate::state::{keybindings, password_config, user_picker, KeyBinding};
+use crate::sys_ctrl::{reboot, shutdown};
+use crate::tui::{
+  draw_help_message, get_accent_message_theme, toggle_password_reveal,
+};
+
+type KeyAction = fn(&mut Cursive);
+
+// dialogs and popups are layers above the greeter
+fn is_greeter_focused(siv: &mut Cursive) -> bool {
+  siv.screen().len() == 1
+}
+
+fn open_select(siv: &mut Cursive, name: &str) {
+  if is_greeter_focused(siv) && siv.focus_name(name).is_ok() {
+    siv.on_event(Event::Key(Key::Enter));
+  }
+}
+
+type ActiveBinding = (&'static KeyBinding, &'static str, KeyAction);
+
+pub fn get_active_bindings() -> Vec<ActiveBinding> {
+  let mut bindings: Vec<(Option<&KeyBinding>, &str, KeyAction)> = vec![
+    (keybindings.help.as_ref(), "Help", draw_help_message),
+    (keybindings.session.as_ref(), "Session", |siv| {
+      open_select(siv, "session")
+    }),
+  ];
+
+  if user_picker.enable {
+    bindings.push((keybindings.user.as_ref(), "User", |siv| {
+      open_select(siv, "user")
+    }));
+  }
+  if password_config.allow_reveal {
+    bindings.push((
+      Some(&password_config.reveal_key),
+      "Show password",
+      toggle_password_reveal,
+    ));
+  }
+
+  bindings.push((keybindings.reboot.as_ref(), "Reboot", |siv| {
+    authorize_power_action(siv)
+      .and_then(|_| reboot())
+      .draw_on_err(siv)
+  }));
+  bindings.push((keybindings.shutdown.as_ref(), "Shutdown", |siv| {
+    authorize_power_action(siv)
+      .and_then(|_| shutdown())
+      .draw_on_err(siv)
+  }));
+
+  bindings
+    .into_iter()
+    .filter_map(|(key_binding, label, action)| {
+      Some((key_binding?, label, action))
+    })
+    .collect()
+}
+
+pub fn bind_global_keys(siv: &mut Cursive) {
+  for (key_binding, _, action) in get_active_bindings() {
+    siv.add_global_callback(key_binding.event.clone(), action);
+  }
+}
+
+pub fn draw_hint_bar(stack: &mut StackView) {
+  if !keybindings.hint_bar {
+    return;
+  }
+
+  let hints = get_active_bindings()
+    .iter()
+    .map(|(key_binding, label, _)| format!("{} {}", key_binding.name, label))
+    .collect::<Vec<_>>()
+    .join("  ");
+
+  stack.add_fullscreen_layer(
+    LinearLayout::vertical()
+      .child(DummyView.full_height())
+      .child(PaddedView::lrtb(
+        2,
+        2,
+        0,
+        1,
+        ThemedView::new(get_accent_message_theme(), TextView::new(hints)),
+      )),
+  );
+}
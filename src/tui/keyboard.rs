@@ -0,0 +1,47 @@
+use cursive::views::TextView;
+use cursive::Cursive;
+use std::thread;
+use std::time::Duration;
+
+use crate::console::is_caps_lock_on;
+use crate::state::keyboard_config;
+
+const CAPS_LOCK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+fn update_caps_lock_warning(siv: &mut Cursive, caps_lock: bool) {
+  let warning = if caps_lock { "CAPS LOCK IS ON" } else { "" };
+
+  siv.call_on_name("caps_lock_warning", |view: &mut TextView| {
+    view.set_content(warning);
+  });
+}
+
+// the lock keys don't send any events, so the state is polled on its own
+// thread, which only wakes the greeter up once the state changes
+pub fn watch_caps_lock(siv: &mut Cursive) {
+  if !keyboard_config.caps_lock_warning {
+    return;
+  }
+
+  let mut caps_lock = is_caps_lock_on();
+  update_caps_lock_warning(siv, caps_lock);
+
+  let cb_sink = siv.cb_sink().clone();
+  thread::spawn(move || loop {
+    thread::sleep(CAPS_LOCK_POLL_INTERVAL);
+
+    let is_on = is_caps_lock_on();
+    if is_on == caps_lock {
+      continue;
+    }
+    caps_lock = is_on;
+
+    let update = Box::new(move |siv: &mut Cursive| {
+      update_caps_lock_warning(siv, is_on);
+    });
+    // the greeter has quit
+    if cb_sink.send(update).is_err() {
+      break;
+    }
+  });
+}
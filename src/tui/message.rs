@@ -1,12 +1,16 @@
 use cursive::align::HAlign;
+use cursive::view::{Nameable, Resizable};
 use cursive::views::{
-  Button, Dialog, LinearLayout, PaddedView, ResizedView, TextView, ThemedView,
+  Button, Dialog, LinearLayout, PaddedView, ResizedView, ScrollView, TextView,
+  ThemedView,
 };
 use cursive::Cursive;
 use std::fs;
 
-use crate::state::error_icon_ascii_art_path;
-use crate::tui::get_error_message_theme;
+use crate::state::{error_icon_ascii_art_path, help_config, sessions};
+use crate::tui::{
+  get_accent_message_theme, get_active_bindings, get_error_message_theme,
+};
 
 pub fn draw_error_message(siv: &mut Cursive, text: &str) {
   let mut error_box_layout = LinearLayout::vertical();
@@ -54,3 +58,58 @@ pub fn draw_error_message(siv: &mut Cursive, text: &str) {
       )),
   )));
 }
+
+pub fn draw_help_message(siv: &mut Cursive) {
+  if siv.debug_name("help").is_some() {
+    return;
+  }
+
+  let mut help_text = String::from("KEYS\n");
+  help_text.push_str("  Tab: Move between the fields and buttons\n");
+  help_text.push_str("  Enter: Open the selected selector or log in\n");
+  for (key_binding, label, _) in get_active_bindings() {
+    help_text.push_str(&format!("  {}: {}\n", key_binding.name, label));
+  }
+
+  if !sessions.is_empty() {
+    help_text.push_str("\nSESSIONS\n");
+    for session in sessions.iter() {
+      match session.description {
+        Some(ref description) => {
+          help_text.push_str(&format!("  {}: {}\n", session.name, description))
+        }
+        None => help_text.push_str(&format!("  {}\n", session.name)),
+      }
+    }
+  }
+
+  if let Some(ref contact) = help_config.contact {
+    help_text.push_str(&format!("\n{}\n", contact));
+  }
+
+  siv.add_layer(
+    Dialog::around(PaddedView::lrtb(
+      0,
+      0,
+      1,
+      1,
+      LinearLayout::vertical()
+        .child(ThemedView::new(
+          get_accent_message_theme(),
+          ScrollView::new(TextView::new(help_text.trim_end())).max_height(20),
+        ))
+        .child(PaddedView::lrtb(
+          0,
+          0,
+          1,
+          0,
+          Button::new_raw("[OK]", |siv| {
+            siv.pop_layer();
+          }),
+        )),
+    ))
+    .title("HELP")
+    .max_width(60)
+    .with_name("help"),
+  );
+}
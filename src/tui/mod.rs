@@ -1,9 +1,23 @@
+mod ansi;
 mod background;
 mod content_box;
+mod idle;
+mod keybindings;
+mod keyboard;
 mod message;
+mod notice;
+mod password;
+mod screensaver;
 mod theme;
 
+pub use ansi::*;
 pub use background::*;
 pub use content_box::*;
+pub use idle::*;
+pub use keybindings::*;
+pub use keyboard::*;
 pub use message::*;
+pub use notice::*;
+pub use password::*;
+pub use screensaver::*;
 pub use theme::*;
@@ -0,0 +1,79 @@
+use cursive::align::HAlign;
+use cursive::view::{Nameable, Resizable};
+use cursive::views::{
+  Button, Dialog, LayerPosition, LinearLayout, PaddedView, ScrollView, TextView,
+};
+use cursive::Cursive;
+use std::fs;
+use std::time::Duration;
+
+use crate::state::{notice_body_path, notice_config};
+use crate::tui::{is_screensaver_shown, on_idle};
+use crate::utils::{get_current_tty, log_message};
+
+fn accept_notice(siv: &mut Cursive) {
+  log_message(&format!(
+    "Usage notice accepted on {}.",
+    get_current_tty().unwrap_or_else(|| "unknown tty".to_string())
+  ));
+  siv.pop_layer();
+}
+
+pub fn show_notice(siv: &mut Cursive) {
+  // the notice is already covering the login form
+  if siv.debug_name("notice").is_some() {
+    return;
+  }
+
+  let body = match &*notice_body_path {
+    Some(ref path) => fs::read_to_string(path).unwrap_or_else(|_| {
+      log_message("Failed to read the usage notice.");
+      String::new()
+    }),
+    None => String::new(),
+  };
+
+  let is_covered = is_screensaver_shown(siv);
+  siv.add_layer(
+    Dialog::around(PaddedView::lrtb(
+      0,
+      0,
+      1,
+      1,
+      LinearLayout::vertical()
+        .child(ScrollView::new(TextView::new(body)).max_height(20))
+        .child(PaddedView::lrtb(
+          0,
+          0,
+          1,
+          0,
+          Button::new_raw(
+            format!("[{}]", notice_config.accept_text),
+            accept_notice,
+          ),
+        )),
+    ))
+    .title(notice_config.title.clone())
+    .title_position(HAlign::Center)
+    .max_width(80)
+    .with_name("notice"),
+  );
+
+  // kept under the screensaver, which still takes the first key
+  if is_covered {
+    siv
+      .screen_mut()
+      .move_layer(LayerPosition::FromFront(0), LayerPosition::FromFront(1));
+  }
+}
+
+pub fn draw_notice(siv: &mut Cursive) {
+  if !notice_config.enable {
+    return;
+  }
+
+  show_notice(siv);
+  if let Some(idle_timeout) = notice_config.idle_timeout {
+    on_idle(siv, Duration::from_secs(idle_timeout), show_notice);
+  }
+}
@@ -0,0 +1,130 @@
+use cursive::style::PaletteStyle;
+use cursive::view::{View, ViewWrapper};
+use cursive::views::EditView;
+use cursive::{wrap_impl, Cursive, Printer};
+use std::sync::Arc;
+use unicode_width::UnicodeWidthStr;
+
+use crate::state::password_config;
+
+// the number of masks shown and the position of the cursor among them, the
+// cursor is kept at the end of the field on overflowing
+fn get_shown_mask(
+  length: usize,
+  cursor: usize,
+  max_shown_length: usize,
+) -> (usize, usize) {
+  let start = (cursor + 1).saturating_sub(max_shown_length);
+  (
+    length.saturating_sub(start).min(max_shown_length),
+    cursor.saturating_sub(start),
+  )
+}
+
+// an edit view that hides its content behind the configured mask
+pub struct PasswordView {
+  view: EditView,
+  revealed: bool,
+}
+
+impl PasswordView {
+  pub fn new(view: EditView) -> Self {
+    PasswordView {
+      // the default mask is drawn by the edit view itself
+      view: view.secret(),
+      revealed: false,
+    }
+  }
+
+  pub fn get_content(&self) -> Arc<String> {
+    self.view.get_content()
+  }
+
+  pub fn clear(&mut self) {
+    self.view.set_content("");
+  }
+
+  pub fn set_revealed(&mut self, revealed: bool) {
+    self.revealed = revealed && password_config.allow_reveal;
+    self.view.set_secret(!self.revealed);
+  }
+
+  fn draw_mask(&self, printer: &Printer) {
+    let mask = &password_config.mask;
+    let content = self.view.get_content();
+    let length = content[..].chars().count();
+    let cursor = content[..self.view.get_cursor()].chars().count();
+
+    // an empty mask shows nothing at all like getty
+    let (shown_length, cursor_offset) = if mask.is_empty() {
+      (0, 0)
+    } else {
+      let (shown_length, shown_cursor) =
+        get_shown_mask(length, cursor, printer.size.x / mask.width().max(1));
+      (shown_length, shown_cursor * mask.width())
+    };
+
+    printer.with_style(PaletteStyle::EditableText, |printer| {
+      printer.print_hline((0, 0), printer.size.x, " ");
+      printer.print_hline((0, 0), shown_length, mask);
+    });
+
+    if printer.focused {
+      printer.with_style(PaletteStyle::EditableTextCursor, |printer| {
+        let cursor_text = if cursor < length && !mask.is_empty() {
+          mask.as_str()
+        } else {
+          " "
+        };
+        printer.print((cursor_offset, 0), cursor_text);
+      });
+    }
+  }
+}
+
+impl ViewWrapper for PasswordView {
+  wrap_impl!(self.view: EditView);
+
+  fn wrap_draw(&self, printer: &Printer) {
+    if self.revealed || password_config.mask == "*" {
+      self.view.draw(printer);
+    } else {
+      self.draw_mask(printer);
+    }
+  }
+}
+
+pub fn toggle_password_reveal(siv: &mut Cursive) {
+  siv.call_on_name("password", |view: &mut PasswordView| {
+    view.set_revealed(!view.revealed);
+  });
+}
+
+pub fn hide_password(siv: &mut Cursive) {
+  siv.call_on_name("password", |view: &mut PasswordView| {
+    view.set_revealed(false);
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shows_the_whole_mask_when_it_fits() {
+    assert_eq!(get_shown_mask(5, 5, 24), (5, 5));
+    assert_eq!(get_shown_mask(5, 2, 24), (5, 2));
+  }
+
+  #[test]
+  fn keeps_the_cursor_in_the_field_on_overflowing() {
+    assert_eq!(get_shown_mask(30, 30, 24), (23, 23));
+    assert_eq!(get_shown_mask(30, 10, 24), (24, 10));
+  }
+
+  #[test]
+  fn does_not_underflow_without_room() {
+    assert_eq!(get_shown_mask(5, 5, 0), (0, 0));
+    assert_eq!(get_shown_mask(0, 0, 0), (0, 0));
+  }
+}
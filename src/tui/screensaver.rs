@@ -0,0 +1,395 @@
+use cursive::event::{Event, EventResult};
+use cursive::theme::{BaseColor, Color, ColorStyle};
+use cursive::view::{Nameable, Resizable};
+use cursive::views::Canvas;
+use cursive::{Cursive, Printer, Vec2};
+use std::num::NonZeroU32;
+use std::time::Instant;
+use text_to_ascii_art::to_art;
+
+use crate::state::ScreensaverKind;
+use crate::tui::{get_background_text, get_hostname_art};
+use crate::utils::{format_local_time, longest_line_length, Random};
+
+const SCREENSAVER_FPS: u32 = 10;
+const STAR_COUNT: usize = 120;
+const STAR_SPEED: f64 = 0.015;
+const MATRIX_CHARACTERS: &[u8] =
+  b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ@#$%&*+=<>";
+const LOGO_COLORS: [BaseColor; 6] = [
+  BaseColor::Green,
+  BaseColor::Cyan,
+  BaseColor::Blue,
+  BaseColor::Magenta,
+  BaseColor::Red,
+  BaseColor::Yellow,
+];
+// frames the clock stays in place, it moves around to prevent burn in
+const CLOCK_MOVE_FRAMES: u64 = 60 * SCREENSAVER_FPS as u64;
+
+// a point in a cube in front of the screen, the screen is at z = 0
+struct Star {
+  x: f64,
+  y: f64,
+  z: f64,
+}
+
+// a falling column of characters
+struct Drop {
+  y: f64,
+  speed: f64,
+  length: usize,
+}
+
+struct Screensaver {
+  kind: ScreensaverKind,
+  size: Vec2,
+  random: Random,
+  start: Instant,
+  frame: u64,
+  stars: Vec<Star>,
+  drops: Vec<Drop>,
+  logo: String,
+  logo_position: Vec2,
+  // moving right and down
+  logo_direction: (bool, bool),
+  logo_color: usize,
+  // where the clock is placed as a fraction of the free space
+  clock_position: (f64, f64),
+}
+
+fn draw_colored(printer: &Printer, position: Vec2, text: &str, color: Color) {
+  printer.with_color(
+    ColorStyle::new(color, Color::Dark(BaseColor::Black)),
+    |printer| printer.print(position, text),
+  );
+}
+
+fn clear_screen(printer: &Printer) {
+  let black = Color::Dark(BaseColor::Black);
+  printer.with_color(ColorStyle::new(black, black), |printer| {
+    for y in 0..printer.size.y {
+      printer.print_hline((0, y), printer.size.x, " ");
+    }
+  });
+}
+
+fn get_logo() -> String {
+  get_background_text()
+    .filter(|art| !art.trim().is_empty())
+    .unwrap_or_else(get_hostname_art)
+}
+
+impl Screensaver {
+  fn new(kind: ScreensaverKind) -> Self {
+    let mut random = Random::from_time();
+    let clock_position = (random.fraction(), random.fraction());
+
+    Screensaver {
+      kind,
+      size: Vec2::zero(),
+      random,
+      start: Instant::now(),
+      frame: 0,
+      stars: Vec::new(),
+      drops: Vec::new(),
+      logo: if kind == ScreensaverKind::BouncingLogo {
+        get_logo()
+      } else {
+        String::new()
+      },
+      logo_position: Vec2::zero(),
+      logo_direction: (true, true),
+      logo_color: 0,
+      clock_position,
+    }
+  }
+
+  fn new_star(&mut self, far: bool) -> Star {
+    Star {
+      x: self.random.fraction() * 2.0 - 1.0,
+      y: self.random.fraction() * 2.0 - 1.0,
+      z: if far {
+        1.0
+      } else {
+        self.random.fraction().max(STAR_SPEED)
+      },
+    }
+  }
+
+  fn new_drop(&mut self) -> Drop {
+    let height = self.size.y.max(1);
+    Drop {
+      y: -(self.random.below(height) as f64),
+      speed: 0.3 + self.random.fraction() * 0.7,
+      length: 4 + self.random.below(height / 2),
+    }
+  }
+
+  // the views are laid out before each draw, so this also moves the
+  // animation along
+  fn layout(&mut self, size: Vec2) {
+    self.resize(size);
+    self.advance();
+  }
+
+  // the frame is picked from the time, as the refresh events may be taken
+  // by the greeter's own callbacks before reaching the screensaver
+  fn advance(&mut self) {
+    let fps = SCREENSAVER_FPS as u64;
+    let elapsed_frames = self.start.elapsed().as_millis() as u64 * fps / 1000;
+
+    // skips ahead instead of catching up on every frame after a stall
+    if elapsed_frames > self.frame + fps {
+      self.frame = elapsed_frames - fps;
+    }
+    while self.frame < elapsed_frames {
+      self.step();
+    }
+  }
+
+  fn resize(&mut self, size: Vec2) {
+    if size == self.size {
+      return;
+    }
+    self.size = size;
+
+    match self.kind {
+      ScreensaverKind::Starfield if self.stars.is_empty() => {
+        self.stars = (0..STAR_COUNT).map(|_| self.new_star(false)).collect();
+      }
+      ScreensaverKind::Matrix => {
+        self.drops = (0..size.x).map(|_| self.new_drop()).collect();
+      }
+      ScreensaverKind::BouncingLogo => {
+        let logo_size = self.logo_size();
+        self.logo_position = Vec2::new(
+          self.random.below(size.x.saturating_sub(logo_size.x)),
+          self.random.below(size.y.saturating_sub(logo_size.y)),
+        );
+      }
+      _ => {}
+    }
+  }
+
+  fn logo_size(&self) -> Vec2 {
+    Vec2::new(longest_line_length(&self.logo), self.logo.lines().count())
+  }
+
+  fn step(&mut self) {
+    self.frame += 1;
+
+    match self.kind {
+      ScreensaverKind::Starfield => {
+        for i in 0..self.stars.len() {
+          self.stars[i].z -= STAR_SPEED;
+          if self.stars[i].z <= STAR_SPEED {
+            self.stars[i] = self.new_star(true);
+          }
+        }
+      }
+      ScreensaverKind::Matrix => {
+        let height = self.size.y as f64;
+        for i in 0..self.drops.len() {
+          self.drops[i].y += self.drops[i].speed;
+          if self.drops[i].y - self.drops[i].length as f64 > height {
+            self.drops[i] = self.new_drop();
+          }
+        }
+      }
+      ScreensaverKind::BouncingLogo => self.step_logo(),
+      ScreensaverKind::Clock => {
+        if self.frame.is_multiple_of(CLOCK_MOVE_FRAMES) {
+          self.clock_position =
+            (self.random.fraction(), self.random.fraction());
+        }
+      }
+    }
+  }
+
+  fn step_logo(&mut self) {
+    let max_position = self.size.saturating_sub(self.logo_size());
+    let (mut right, mut down) = self.logo_direction;
+    let mut bounced = false;
+
+    if (right && self.logo_position.x >= max_position.x)
+      || (!right && self.logo_position.x == 0)
+    {
+      right = !right;
+      bounced = true;
+    }
+    if (down && self.logo_position.y >= max_position.y)
+      || (!down && self.logo_position.y == 0)
+    {
+      down = !down;
+      bounced = true;
+    }
+
+    // the logo fills the screen on an axis it can't move on
+    if max_position.x > 0 {
+      self.logo_position.x = if right {
+        self.logo_position.x + 1
+      } else {
+        self.logo_position.x - 1
+      };
+    }
+    if max_position.y > 0 {
+      self.logo_position.y = if down {
+        self.logo_position.y + 1
+      } else {
+        self.logo_position.y - 1
+      };
+    }
+
+    self.logo_direction = (right, down);
+    if bounced {
+      self.logo_color = (self.logo_color + 1) % LOGO_COLORS.len();
+    }
+  }
+
+  fn draw_starfield(&self, printer: &Printer) {
+    let center_x = printer.size.x as f64 / 2.0;
+    let center_y = printer.size.y as f64 / 2.0;
+
+    for star in &self.stars {
+      let x = center_x + star.x / star.z * center_x;
+      let y = center_y + star.y / star.z * center_y;
+      if x < 0.0 || y < 0.0 {
+        continue;
+      }
+      let position = Vec2::new(x as usize, y as usize);
+      if position.x >= printer.size.x || position.y >= printer.size.y {
+        continue;
+      }
+
+      let (text, color) = match star.z {
+        z if z > 0.66 => (".", Color::Dark(BaseColor::White)),
+        z if z > 0.33 => ("+", Color::Dark(BaseColor::White)),
+        _ => ("*", Color::Light(BaseColor::White)),
+      };
+      draw_colored(printer, position, text, color);
+    }
+  }
+
+  fn draw_matrix(&self, printer: &Printer) {
+    for (x, drop) in self.drops.iter().enumerate() {
+      let head = drop.y as isize;
+
+      for i in 0..drop.length {
+        let y = head - i as isize;
+        if y < 0 || y as usize >= printer.size.y {
+          continue;
+        }
+
+        // the characters change slowly while falling
+        let seed = x * 31 + y as usize * 17 + (self.frame / 3) as usize;
+        let c = MATRIX_CHARACTERS
+          [seed.wrapping_mul(2654435761) % MATRIX_CHARACTERS.len()];
+        let color = match i {
+          0 => Color::Light(BaseColor::White),
+          1..=3 => Color::Light(BaseColor::Green),
+          _ => Color::Dark(BaseColor::Green),
+        };
+        draw_colored(
+          printer,
+          Vec2::new(x, y as usize),
+          &(c as char).to_string(),
+          color,
+        );
+      }
+    }
+  }
+
+  fn draw_logo(&self, printer: &Printer) {
+    let color = Color::Light(LOGO_COLORS[self.logo_color]);
+    for (i, line) in self.logo.lines().enumerate() {
+      draw_colored(printer, self.logo_position + (0, i), line, color);
+    }
+  }
+
+  fn draw_clock(&self, printer: &Printer) {
+    let time = format_local_time("%H:%M:%S");
+    let time = to_art(time.clone(), "standard", 0, 1, 0).unwrap_or(time);
+    let date = format_local_time("%a %b %e %Y");
+
+    let width = longest_line_length(&time).max(date.len());
+    let height = time.lines().count() + 1;
+    let free_space = printer.size.saturating_sub((width, height));
+    let position = Vec2::new(
+      (free_space.x as f64 * self.clock_position.0) as usize,
+      (free_space.y as f64 * self.clock_position.1) as usize,
+    );
+
+    let color = Color::Dark(BaseColor::Green);
+    for (i, line) in time.lines().enumerate() {
+      draw_colored(printer, position + (0, i), line, color);
+    }
+    draw_colored(
+      printer,
+      position + ((width.saturating_sub(date.len())) / 2, height - 1),
+      &date,
+      Color::Dark(BaseColor::White),
+    );
+  }
+
+  fn draw(&self, printer: &Printer) {
+    clear_screen(printer);
+    match self.kind {
+      ScreensaverKind::Starfield => self.draw_starfield(printer),
+      ScreensaverKind::Matrix => self.draw_matrix(printer),
+      ScreensaverKind::BouncingLogo => self.draw_logo(printer),
+      ScreensaverKind::Clock => self.draw_clock(printer),
+    }
+  }
+}
+
+// any input closes the screensaver without reaching the greeter
+fn is_dismiss_event(event: &Event) -> bool {
+  !matches!(event, Event::Refresh | Event::WindowResize)
+}
+
+fn dismiss(previous_fps: Option<NonZeroU32>) -> EventResult {
+  EventResult::with_cb(move |siv| {
+    siv.pop_layer();
+    siv.set_fps(previous_fps.map_or(0, NonZeroU32::get));
+  })
+}
+
+// the blank screen counts as a screensaver as well
+pub fn is_screensaver_shown(siv: &mut Cursive) -> bool {
+  siv.debug_name("screensaver").is_some()
+}
+
+pub fn draw_blank_screen(siv: &mut Cursive) {
+  let previous_fps = siv.fps();
+  let blank_screen = Canvas::new(())
+    .with_draw(|_, printer: &Printer| clear_screen(printer))
+    .with_on_event(move |_, event| {
+      if is_dismiss_event(&event) {
+        dismiss(previous_fps)
+      } else {
+        EventResult::Ignored
+      }
+    });
+
+  siv.add_fullscreen_layer(blank_screen.full_screen().with_name("screensaver"));
+}
+
+pub fn draw_screensaver(siv: &mut Cursive, kind: ScreensaverKind) {
+  // the animation only runs while the screensaver is shown
+  let previous_fps = siv.fps();
+  siv.set_fps(SCREENSAVER_FPS);
+
+  let screensaver = Canvas::new(Screensaver::new(kind))
+    .with_draw(|screensaver, printer| screensaver.draw(printer))
+    .with_layout(|screensaver, size| screensaver.layout(size))
+    .with_on_event(move |_, event| {
+      if is_dismiss_event(&event) {
+        dismiss(previous_fps)
+      } else {
+        EventResult::Ignored
+      }
+    });
+
+  siv.add_fullscreen_layer(screensaver.full_screen().with_name("screensaver"));
+}
@@ -1,3 +1,8 @@
+use cursive::utils::lines::simple::LinesIterator;
+use nix::libc;
+use std::ffi::CString;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
 use unicode_width::UnicodeWidthStr;
 
 pub fn longest_line_length(input: &str) -> usize {
@@ -8,6 +13,12 @@ pub fn longest_line_length(input: &str) -> usize {
     .unwrap_or(0)
 }
 
+// number of lines the text takes up when wrapped at the given width, wrapped
+// between words the same way a text view does
+pub fn count_wrapped_lines(input: &str, width: usize) -> usize {
+  LinesIterator::new(input, width.max(1)).count()
+}
+
 // checks for the format tty{number}
 fn is_tty(s: &str) -> bool {
   s.starts_with("tty") && s[3..].chars().all(|c| c.is_digit(10))
@@ -17,6 +28,19 @@ pub fn get_current_tty_path() -> std::io::Result<std::path::PathBuf> {
   std::fs::read_link("/proc/self/fd/0")
 }
 
+// the width and height of the terminal in characters
+pub fn get_terminal_size() -> Option<(usize, usize)> {
+  let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+
+  unsafe {
+    if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) < 0 {
+      return None;
+    }
+  }
+
+  Some((size.ws_col as usize, size.ws_row as usize))
+}
+
 pub fn get_current_tty() -> Option<String> {
   if let Ok(path) = get_current_tty_path() {
     let tty = path.file_name().unwrap().to_string_lossy().to_string();
@@ -28,3 +52,128 @@ pub fn get_current_tty() -> Option<String> {
 
   None
 }
+
+// a small xorshift generator, good enough for animations and picking art
+pub struct Random(u64);
+
+impl Random {
+  pub fn from_time() -> Self {
+    let seed = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_nanos() as u64)
+      .unwrap_or(0);
+    // the state of xorshift must never be zero
+    Random(seed | 1)
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+
+  // a number in 0..limit, or 0 if the limit is 0
+  pub fn below(&mut self, limit: usize) -> usize {
+    if limit == 0 {
+      return 0;
+    }
+    (self.next_u64() % limit as u64) as usize
+  }
+
+  // a number in 0.0..1.0
+  pub fn fraction(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+}
+
+pub fn get_unix_time() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or(0)
+}
+
+// formats the current local time with strftime(3) format specifiers
+pub fn format_local_time(format: &str) -> String {
+  let Ok(c_format) = CString::new(format) else {
+    return String::new();
+  };
+  let mut buffer = [0u8; 256];
+
+  let length = unsafe {
+    let now = libc::time(std::ptr::null_mut());
+    let mut local_time: libc::tm = std::mem::zeroed();
+    if libc::localtime_r(&now, &mut local_time).is_null() {
+      return String::new();
+    }
+
+    libc::strftime(
+      buffer.as_mut_ptr() as *mut libc::c_char,
+      buffer.len(),
+      c_format.as_ptr(),
+      &local_time,
+    )
+  };
+
+  String::from_utf8_lossy(&buffer[..length]).to_string()
+}
+
+// reads a field like NAME or PRETTY_NAME from os-release(5)
+pub fn get_os_release_field(field: &str) -> Option<String> {
+  let os_release = fs::read_to_string("/etc/os-release")
+    .or_else(|_| fs::read_to_string("/usr/lib/os-release"))
+    .ok()?;
+
+  os_release.lines().find_map(|line| {
+    let (key, value) = line.split_once('=')?;
+    if key.trim() == field {
+      Some(
+        value
+          .trim()
+          .trim_matches('"')
+          .trim_matches('\'')
+          .to_string(),
+      )
+    } else {
+      None
+    }
+  })
+}
+
+// logs to the authpriv facility of syslog
+pub fn log_message(message: &str) {
+  let Ok(c_message) = CString::new(message) else {
+    return;
+  };
+
+  unsafe {
+    libc::openlog(c"tuilog".as_ptr(), libc::LOG_PID, libc::LOG_AUTHPRIV);
+    libc::syslog(libc::LOG_NOTICE, c"%s".as_ptr(), c_message.as_ptr());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn counts_lines_that_fit() {
+    assert_eq!(count_wrapped_lines("", 10), 0);
+    assert_eq!(count_wrapped_lines("hello", 10), 1);
+    assert_eq!(count_wrapped_lines("hello\nworld", 10), 2);
+  }
+
+  #[test]
+  fn counts_lines_wrapped_between_words() {
+    // 11 characters fit in 2 lines of 6 but the words don't
+    assert_eq!(count_wrapped_lines("abc defg hi", 6), 3);
+    assert_eq!(count_wrapped_lines("a long session info", 10), 3);
+  }
+
+  #[test]
+  fn counts_words_longer_than_the_width() {
+    assert_eq!(count_wrapped_lines("abcdefghij", 4), 3);
+    assert_eq!(count_wrapped_lines("abc", 0), 3);
+  }
+}